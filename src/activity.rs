@@ -0,0 +1,128 @@
+use core::{
+    future::Future,
+    pin::Pin,
+    sync::atomic::Ordering,
+    task::{Context, Poll},
+};
+#[cfg(feature = "stream")]
+use futures_core::Stream;
+
+use crate::{runtime::Runtime, CowTimeout, Timeout};
+
+/// A registration in a timeout's list of activity listeners
+pub(crate) struct Listener<'a, R: Runtime> {
+    timeout: CowTimeout<'a, R>,
+    // The number of resets that have already been observed
+    seen: u64,
+    // The key in the timeout's activity listener list
+    key: Option<usize>,
+}
+
+impl<'a, R: Runtime> Listener<'a, R> {
+    pub(crate) fn new(timeout: CowTimeout<'a, R>) -> Self {
+        let seen = timeout.as_ref().resets.load(Ordering::SeqCst);
+        Self {
+            timeout,
+            seen,
+            key: None,
+        }
+    }
+
+    /// Poll for resets, returning how many happened since the last time this returned
+    /// [`Poll::Ready`]
+    pub(crate) fn poll_resets(&mut self, cx: &mut Context<'_>) -> Poll<u64> {
+        let timeout = self.timeout.as_ref();
+        if self.key.is_none() {
+            timeout.listeners.fetch_add(1, Ordering::SeqCst);
+        }
+        timeout.activity.lock().register(&mut self.key, cx.waker());
+        // A concurrent reset either sees the listener count above, or is seen here
+        let resets = timeout.resets.load(Ordering::SeqCst);
+        if resets == self.seen {
+            return Poll::Pending;
+        }
+        self.unregister();
+        let count = resets.wrapping_sub(self.seen);
+        self.seen = resets;
+        Poll::Ready(count)
+    }
+
+    pub(crate) fn timeout(&self) -> &Timeout<R> {
+        self.timeout.as_ref()
+    }
+
+    fn unregister(&mut self) {
+        if let Some(key) = self.key.take() {
+            let timeout = self.timeout.as_ref();
+            timeout.activity.lock().remove(key);
+            timeout.listeners.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
+impl<R: Runtime> Drop for Listener<'_, R> {
+    fn drop(&mut self) {
+        self.unregister();
+    }
+}
+
+/// A future that completes the next time a [`Timeout`] is [reset](Timeout::reset).
+///
+/// Created by [`Timeout::wait_for_activity`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Activity<'a, R: Runtime> {
+    listener: Listener<'a, R>,
+}
+
+impl<'a, R: Runtime> Activity<'a, R> {
+    pub(crate) fn new(timeout: CowTimeout<'a, R>) -> Self {
+        Self {
+            listener: Listener::new(timeout),
+        }
+    }
+
+    /// The timeout reference
+    pub fn timeout(&self) -> &Timeout<R> {
+        self.listener.timeout()
+    }
+}
+
+impl<R: Runtime> Future for Activity<'_, R> {
+    type Output = ();
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.get_mut().listener.poll_resets(cx).map(|_| ())
+    }
+}
+
+/// A stream that yields every time a [`Timeout`] is [reset](Timeout::reset).
+///
+/// Created by [`Timeout::activity`]. Resets that happen while the stream isn't polled are
+/// coalesced: each item is the number of resets since the previous one. The stream never ends.
+#[cfg(feature = "stream")]
+#[must_use = "streams do nothing unless polled"]
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+pub struct ActivityStream<'a, R: Runtime> {
+    listener: Listener<'a, R>,
+}
+
+#[cfg(feature = "stream")]
+impl<'a, R: Runtime> ActivityStream<'a, R> {
+    pub(crate) fn new(timeout: CowTimeout<'a, R>) -> Self {
+        Self {
+            listener: Listener::new(timeout),
+        }
+    }
+
+    /// The timeout reference
+    pub fn timeout(&self) -> &Timeout<R> {
+        self.listener.timeout()
+    }
+}
+
+#[cfg(feature = "stream")]
+impl<R: Runtime> Stream for ActivityStream<'_, R> {
+    type Item = u64;
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().listener.poll_resets(cx).map(Some)
+    }
+}
@@ -0,0 +1,141 @@
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use crate::{checked_nanos, runtime::Runtime, Error, Timeout};
+
+/// A builder for [`Timeout`] configuration.
+///
+/// Created by [`Timeout::builder`]. Unlike the shorthand constructors, [`Builder::build`]
+/// validates the configuration and reports errors instead of panicking or saturating.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use async_shared_timeout::{runtime, Timeout};
+///
+/// let timeout = Timeout::builder(runtime::Tokio::new())
+///     .initial_timeout(Duration::from_secs(5))
+///     .default_timeout(Duration::from_secs(10))
+///     .max_lifetime(Duration::from_secs(3600))
+///     .build()
+///     .unwrap();
+/// ```
+///
+/// Phases can be used to follow the state of a protocol, e.g. SMTP (see RFC 5321, section
+/// 4.5.3.2):
+///
+/// ```
+/// use std::time::Duration;
+/// use async_shared_timeout::{runtime, Timeout};
+///
+/// let timeout = Timeout::builder(runtime::Tokio::new())
+///     .phase("greeting", Duration::from_secs(5 * 60))
+///     .phase("mail", Duration::from_secs(5 * 60))
+///     .phase("data-block", Duration::from_secs(3 * 60))
+///     .phase("data-termination", Duration::from_secs(10 * 60))
+///     .start_phase("greeting")
+///     .build()
+///     .unwrap();
+/// // Upon receiving the DATA command...
+/// timeout.set_phase_and_reset("data-block").unwrap();
+/// assert_eq!(timeout.default_timeout(), Duration::from_secs(3 * 60));
+/// ```
+#[derive(Debug, Clone)]
+#[must_use = "the builder does nothing unless `build` is called"]
+pub struct Builder<R: Runtime> {
+    runtime: R,
+    default_timeout: Option<Duration>,
+    initial_timeout: Option<Duration>,
+    max_lifetime: Option<Duration>,
+    phases: Vec<(&'static str, Duration)>,
+    start_phase: Option<&'static str>,
+}
+
+impl<R: Runtime> Builder<R> {
+    pub(crate) fn new(runtime: R) -> Self {
+        Self {
+            runtime,
+            default_timeout: None,
+            initial_timeout: None,
+            max_lifetime: None,
+            phases: Vec::new(),
+            start_phase: None,
+        }
+    }
+    /// Set the default timeout, see [`Timeout::new`]. Exactly one of this and the
+    /// [starting phase](Builder::start_phase) must be set.
+    pub fn default_timeout(mut self, default_timeout: Duration) -> Self {
+        self.default_timeout = Some(default_timeout);
+        self
+    }
+    /// Set the initial timeout, see [`Timeout::with_initial_timeout`]
+    pub fn initial_timeout(mut self, initial_timeout: Duration) -> Self {
+        self.initial_timeout = Some(initial_timeout);
+        self
+    }
+    /// Set the maximum lifetime, see [`Timeout::with_max_lifetime`]
+    pub fn max_lifetime(mut self, max_lifetime: Duration) -> Self {
+        self.max_lifetime = Some(max_lifetime);
+        self
+    }
+    /// Add a named phase with its own default timeout, see [`Timeout::set_phase`]
+    pub fn phase(mut self, name: &'static str, default_timeout: Duration) -> Self {
+        self.phases.push((name, default_timeout));
+        self
+    }
+    /// Start in the given phase, using its timeout as the default timeout. This can't be combined
+    /// with [`Builder::default_timeout`].
+    pub fn start_phase(mut self, name: &'static str) -> Self {
+        self.start_phase = Some(name);
+        self
+    }
+    /// Create the timeout. Its lifetime starts now rather than at the creation of the builder.
+    ///
+    /// # Errors
+    /// - [`Error::MissingDefaultTimeout`] if neither the default timeout nor the starting phase
+    ///   was set
+    /// - [`Error::ConflictingDefaultTimeout`] if both the default timeout and the starting phase
+    ///   were set
+    /// - [`Error::DurationTooLong`] if any of the durations is longer than ~584 years
+    /// - [`Error::DuplicatePhase`] if several phases have the same name
+    /// - [`Error::UnknownPhase`] if the starting phase wasn't added
+    pub fn build(self) -> Result<Timeout<R>, Error> {
+        let mut phases = Vec::with_capacity(self.phases.len());
+        for (name, default_timeout) in self.phases {
+            if phases.iter().any(|(x, _)| *x == name) {
+                return Err(Error::DuplicatePhase);
+            }
+            phases.push((name, checked_nanos(default_timeout)?));
+        }
+        let start_phase = self
+            .start_phase
+            .map(|name| {
+                phases
+                    .iter()
+                    .position(|(x, _)| *x == name)
+                    .ok_or(Error::UnknownPhase)
+            })
+            .transpose()?;
+        let default_timeout = match (start_phase, self.default_timeout) {
+            (Some(_), Some(_)) => return Err(Error::ConflictingDefaultTimeout),
+            (Some(phase), None) => phases[phase].1,
+            (None, Some(default_timeout)) => checked_nanos(default_timeout)?,
+            (None, None) => return Err(Error::MissingDefaultTimeout),
+        };
+        let initial_timeout = self.initial_timeout.map(checked_nanos).transpose()?;
+        let max_lifetime = self.max_lifetime.map(checked_nanos).transpose()?;
+        let mut timeout = Timeout::with_nanos(self.runtime, default_timeout);
+        timeout.phases = phases;
+        if let Some(phase) = start_phase {
+            *timeout.phase.get_mut() = phase;
+        }
+        if let Some(initial_timeout) = initial_timeout {
+            timeout.set_initial_timeout_nanos(initial_timeout);
+        }
+        if let Some(max_lifetime) = max_lifetime {
+            timeout.set_max_lifetime_nanos(max_lifetime);
+        }
+        Ok(timeout)
+    }
+}
@@ -0,0 +1,95 @@
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use futures_core::{FusedStream, Stream};
+
+use crate::{activity::Listener, runtime::Runtime, CowTimeout, Timeout};
+
+/// A stream of the time left until a [`Timeout`] expires.
+///
+/// Created by [`Timeout::countdown`]. The time left is yielded right away, then once every tick.
+/// Every [reset](Timeout::reset) yields the new time left immediately and restarts the ticks
+/// from there. The stream ends once the timeout expires.
+#[must_use = "streams do nothing unless polled"]
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+pub struct Countdown<'a, R: Runtime> {
+    // Used to get notified about resets
+    listener: Listener<'a, R>,
+    tick_ns: u64,
+    // The time of the next tick, or 0 if one is due right away
+    next_tick_ns: u64,
+    // The key in the timeout's waiter list, used to get notified upon expiry
+    key: Option<usize>,
+    done: bool,
+}
+
+impl<'a, R: Runtime> Countdown<'a, R> {
+    pub(crate) fn new(timeout: CowTimeout<'a, R>, tick_ns: u64) -> Self {
+        Self {
+            listener: Listener::new(timeout),
+            tick_ns,
+            next_tick_ns: 0,
+            key: None,
+            done: false,
+        }
+    }
+
+    /// The timeout reference
+    pub fn timeout(&self) -> &Timeout<R> {
+        self.listener.timeout()
+    }
+}
+
+impl<R: Runtime> Stream for Countdown<'_, R> {
+    type Item = Duration;
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(None);
+        }
+        if this.listener.poll_resets(cx).is_ready() {
+            this.next_tick_ns = 0;
+        }
+        let timeout = this.listener.timeout();
+        timeout.waiters.lock().register(&mut this.key, cx.waker());
+        loop {
+            if timeout.is_expired() {
+                this.done = true;
+                if let Some(key) = this.key.take() {
+                    timeout.waiters.lock().remove(key);
+                }
+                return Poll::Ready(None);
+            }
+            let elapsed = timeout.elapsed_nanos();
+            if elapsed >= this.next_tick_ns {
+                this.next_tick_ns = elapsed.saturating_add(this.tick_ns);
+                // Activity guards may keep the timeout from expiring past its deadline
+                return Poll::Ready(Some(timeout.remaining().unwrap_or_default()));
+            }
+            // Wake up at the deadline too, to end the stream right away
+            let target = this.next_tick_ns.min(timeout.arm());
+            if target == u64::MAX {
+                return Poll::Pending;
+            }
+            if timeout.poll_sleep_until(target, elapsed).is_pending() {
+                return Poll::Pending;
+            }
+        }
+    }
+}
+
+impl<R: Runtime> FusedStream for Countdown<'_, R> {
+    fn is_terminated(&self) -> bool {
+        self.done
+    }
+}
+
+impl<R: Runtime> Drop for Countdown<'_, R> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.listener.timeout().waiters.lock().remove(key);
+        }
+    }
+}
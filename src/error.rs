@@ -0,0 +1,86 @@
+use core::fmt;
+
+use crate::ExpiryReason;
+
+/// An error returned by the fallible [`Timeout`](crate::Timeout) methods
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Error {
+    /// The duration can't be represented in nanoseconds by a `u64` (it's longer than ~584 years)
+    DurationTooLong,
+    /// The default timeout wasn't set when building a timeout
+    MissingDefaultTimeout,
+    /// The phase wasn't configured for this timeout
+    UnknownPhase,
+    /// Several phases with the same name were configured
+    DuplicatePhase,
+    /// Both the default timeout and the starting phase were set when building a timeout
+    ConflictingDefaultTimeout,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DurationTooLong => f.write_str("duration is too long"),
+            Self::MissingDefaultTimeout => f.write_str("default timeout is not set"),
+            Self::UnknownPhase => f.write_str("unknown phase"),
+            Self::DuplicatePhase => f.write_str("duplicate phase"),
+            Self::ConflictingDefaultTimeout => {
+                f.write_str("both the default timeout and the starting phase are set")
+            }
+        }
+    }
+}
+
+// Available whenever the crate isn't `no_std`
+#[cfg(any(feature = "std", feature = "tokio", feature = "async-io"))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(any(feature = "std", feature = "tokio", feature = "async-io")))
+)]
+impl std::error::Error for Error {}
+
+/// The error returned by [`Timeout::run`](crate::Timeout::run) when the timeout expires before
+/// the future completes
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Elapsed {
+    reason: ExpiryReason,
+}
+
+impl Elapsed {
+    pub(crate) fn new(reason: ExpiryReason) -> Self {
+        Self { reason }
+    }
+    /// Why the timeout has expired
+    pub fn reason(&self) -> ExpiryReason {
+        self.reason
+    }
+}
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.reason {
+            ExpiryReason::Idle => f.write_str("idle timeout elapsed"),
+            ExpiryReason::MaxLifetime => f.write_str("maximum lifetime elapsed"),
+            ExpiryReason::Cancelled => f.write_str("timeout cancelled"),
+        }
+    }
+}
+
+#[cfg(any(feature = "std", feature = "tokio", feature = "async-io"))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(any(feature = "std", feature = "tokio", feature = "async-io")))
+)]
+impl std::error::Error for Elapsed {}
+
+#[cfg(any(feature = "std", feature = "tokio", feature = "async-io"))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(any(feature = "std", feature = "tokio", feature = "async-io")))
+)]
+impl From<Elapsed> for std::io::Error {
+    fn from(err: Elapsed) -> Self {
+        Self::new(std::io::ErrorKind::TimedOut, err)
+    }
+}
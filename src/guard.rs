@@ -0,0 +1,43 @@
+use core::sync::atomic::Ordering;
+
+use crate::{runtime::Runtime, CowTimeout, Timeout};
+
+/// A guard that keeps a [`Timeout`] from expiring while it's alive.
+///
+/// Created by [`Timeout::activity_guard`], or by [`SharedTimeout::activity_guard`] for a guard
+/// that keeps the timeout alive rather than borrowing it. Any number of guards may exist at once,
+/// possibly in different tasks. When the last one is dropped, the timeout is
+/// [reset](Timeout::reset), unless it was expired with [`Timeout::expire_now`] in the meantime.
+///
+/// [`SharedTimeout::activity_guard`]: crate::SharedTimeout::activity_guard
+#[must_use = "the timeout can expire again as soon as the guard is dropped"]
+pub struct ActivityGuard<'a, R: Runtime> {
+    timeout: CowTimeout<'a, R>,
+}
+
+impl<'a, R: Runtime> ActivityGuard<'a, R> {
+    pub(crate) fn new(timeout: CowTimeout<'a, R>) -> Self {
+        timeout
+            .as_ref()
+            .active_guards
+            .fetch_add(1, Ordering::SeqCst);
+        Self { timeout }
+    }
+    /// The timeout reference
+    pub fn timeout(&self) -> &Timeout<R> {
+        self.timeout.as_ref()
+    }
+}
+
+impl<R: Runtime> Drop for ActivityGuard<'_, R> {
+    fn drop(&mut self) {
+        let timeout = self.timeout.as_ref();
+        if timeout.active_guards.fetch_sub(1, Ordering::SeqCst) == 1 {
+            if !timeout.is_cancelled() {
+                timeout.reset();
+            }
+            // The waiters wait for an explicit wakeup while guards are held
+            timeout.wake_waiters();
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use core::{
+    pin::Pin,
+    sync::atomic::Ordering,
+    task::{Context, Poll},
+    time::Duration,
+};
+use futures_core::{FusedStream, Stream};
+
+use crate::{runtime::Runtime, CowTimeout, Timeout};
+
+/// A stream that yields when a [`Timeout`] hasn't been reset for a given interval.
+///
+/// Created by [`Timeout::heartbeats`]. Each item is the time since the last
+/// [reset](Timeout::reset). While the timeout is being reset, nothing is yielded; while it isn't,
+/// an item is yielded every interval. The stream ends once the timeout expires.
+#[must_use = "streams do nothing unless polled"]
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+pub struct Heartbeats<'a, R: Runtime> {
+    timeout: CowTimeout<'a, R>,
+    interval_ns: u64,
+    last_beat_ns: u64,
+    // The key in the timeout's waiter list, used to get notified upon expiry
+    key: Option<usize>,
+    done: bool,
+}
+
+impl<'a, R: Runtime> Heartbeats<'a, R> {
+    pub(crate) fn new(timeout: CowTimeout<'a, R>, interval_ns: u64) -> Self {
+        Self {
+            timeout,
+            interval_ns,
+            last_beat_ns: 0,
+            key: None,
+            done: false,
+        }
+    }
+
+    /// The timeout reference
+    pub fn timeout(&self) -> &Timeout<R> {
+        self.timeout.as_ref()
+    }
+}
+
+impl<R: Runtime> Stream for Heartbeats<'_, R> {
+    type Item = Duration;
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(None);
+        }
+        let timeout = this.timeout.as_ref();
+        // Registering is what makes expiring early and moving the deadline earlier wake us up
+        timeout.waiters.lock().register(&mut this.key, cx.waker());
+        loop {
+            let deadline = timeout.arm();
+            let elapsed = timeout.elapsed_nanos();
+            if elapsed >= deadline {
+                this.done = true;
+                if let Some(key) = this.key.take() {
+                    timeout.waiters.lock().remove(key);
+                }
+                return Poll::Ready(None);
+            }
+            // Resets only ever move the last reset forward. If one happens while sleeping, the
+            // sleep fires before the heartbeat is due, and is re-armed here.
+            let last_reset = timeout.last_reset_from_epoch_ns.load(Ordering::Acquire);
+            let due = last_reset
+                .max(this.last_beat_ns)
+                .saturating_add(this.interval_ns);
+            if elapsed >= due {
+                this.last_beat_ns = elapsed;
+                return Poll::Ready(Some(Duration::from_nanos(elapsed - last_reset)));
+            }
+            // Wake up at the deadline too, to end the stream right away
+            let target = due.min(deadline);
+            if target == u64::MAX {
+                return Poll::Pending;
+            }
+            if timeout.poll_sleep_until(target, elapsed).is_pending() {
+                return Poll::Pending;
+            }
+        }
+    }
+}
+
+impl<R: Runtime> FusedStream for Heartbeats<'_, R> {
+    fn is_terminated(&self) -> bool {
+        self.done
+    }
+}
+
+impl<R: Runtime> Drop for Heartbeats<'_, R> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.timeout.as_ref().waiters.lock().remove(key);
+        }
+    }
+}
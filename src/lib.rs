@@ -16,35 +16,94 @@
 //! **Wrapper**
 //!
 //! - `wrapper` - enable a wrapper around types that you can use for easier resetting. By default,
-//!               only future support is enabled (reset the timer upon future completion).
+//!   only future support is enabled (reset the timer upon future completion).
 //! - `read-write` - enable async `Read`/`Write` trait support for the wrapper (reset the timer
-//!                  upon successful read/write operations)
-//! - `stream` - enable `Stream` support for the wrapper (reset the timer upon stream advancement).
+//!   upon successful read/write operations)
+//! - `stream` - enable `Stream` support for the wrapper (reset the timer upon stream advancement),
+//!   and streams of timeout events such as `Timeout::watchdog`.
 //!
 //! **Integration with other runtimes**
 //!
-//! - `std` (enabled by default) - enable `std` integration. Currently it's only used to enable
-//!                                `Arc` and `AsRawFd` support for the wrapper.
+//! - `std` (enabled by default) - enable `std` integration: `Arc` and `AsRawFd` support for the
+//!   wrapper, owned timeout handles, and blocking instead of spinning on the internal locks.
 //! - `tokio` (enabled by default) - [`tokio`](https://docs.rs/tokio) support
 //! - `async-io` - support [`async-io`](https://docs.rs/async-io) as the timer runtime.
 //! - `futures-io` - support [`futures-io`](https://docs.rs/futures-io) traits.
 //! - `async-std` - [`async-std`](https://docs.rs/async-std) support (enables `async-io` and `futures-io`).
 //!
+//! Without `std`, `tokio` and `async-io`, the crate is `no_std`. It still needs an allocator
+//! (the `alloc` crate) to keep track of the tasks waiting on a timeout.
+//!
 //! See struct documentation for examples.
+extern crate alloc;
+
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
 use core::{
     future::Future,
-    pin::Pin,
     sync::atomic::Ordering,
-    task::{Context, Poll},
+    task::{Poll, Waker},
     time::Duration,
 };
-use portable_atomic::AtomicU64;
+use portable_atomic::{AtomicBool, AtomicU64, AtomicUsize};
 
+mod activity;
+mod builder;
+#[cfg(feature = "stream")]
+mod countdown;
+mod error;
+mod guard;
+#[cfg(feature = "stream")]
+mod heartbeats;
+mod run;
 pub mod runtime;
-use runtime::{Instant, Runtime, Sleep};
+#[cfg(feature = "std")]
+mod shared;
+mod sync;
+mod wait;
+mod waiters;
+mod warning;
+#[cfg(feature = "stream")]
+mod watchdog;
+use runtime::{Instant, Runtime};
+use sync::Mutex;
+use waiters::{SharedSleep, Waiters};
+
+pub use activity::Activity;
+#[cfg(feature = "stream")]
+pub use activity::ActivityStream;
+pub use builder::Builder;
+#[cfg(feature = "stream")]
+pub use countdown::Countdown;
+pub use error::{Elapsed, Error};
+pub use guard::ActivityGuard;
+#[cfg(feature = "stream")]
+pub use heartbeats::Heartbeats;
+pub use run::Run;
+#[cfg(feature = "std")]
+pub use shared::{SharedTimeout, TimeoutHandle, WeakTimeout};
+pub use wait::Wait;
+pub use warning::Warning;
+#[cfg(feature = "stream")]
+pub use watchdog::{Expiry, Watchdog};
+
+/// Convert a duration to nanoseconds, saturating to `u64::MAX` (which means "never")
+fn saturating_nanos(duration: Duration) -> u64 {
+    u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX)
+}
+
+fn checked_nanos(duration: Duration) -> Result<u64, Error> {
+    u64::try_from(duration.as_nanos()).map_err(|_| Error::DurationTooLong)
+}
+
+/// A hook registered with [`Timeout::on_expire`]
+type Callback = Box<dyn FnOnce(ExpiryReason, Duration) + Send>;
 
 /// A shared timeout.
 ///
+/// Durations and instants are stored as nanoseconds since the timeout was created. Anything
+/// that doesn't fit (~584 years) is treated as infinite, i.e. a timeout with a default timeout
+/// of [`Duration::MAX`] never expires unless reset with a shorter duration.
+///
 /// # Example
 ///
 /// ```
@@ -75,8 +134,78 @@ use runtime::{Instant, Runtime, Sleep};
 pub struct Timeout<R: Runtime> {
     runtime: R,
     epoch: R::Instant,
+    last_reset_from_epoch_ns: AtomicU64,
     timeout_from_epoch_ns: AtomicU64,
+    /// The default timeout used while no phase is set
     default_timeout: AtomicU64,
+    /// The frozen remaining time while paused, or `u64::MAX` if not paused
+    paused_remaining_ns: AtomicU64,
+    /// Whether an initial timeout is in effect, i.e. the timeout hasn't been reset yet
+    initial: AtomicBool,
+    /// Named default timeouts, see [`Timeout::set_phase`]
+    phases: Vec<(&'static str, u64)>,
+    /// The index of the current phase, which determines the default timeout, or `usize::MAX` if
+    /// there's none
+    phase: AtomicUsize,
+    /// The hard deadline, or `u64::MAX` if there's none
+    max_lifetime_ns: AtomicU64,
+    /// The number of [`Timeout::expire_now`] calls so far. Whether it was ever called is only
+    /// relevant while the deadline is 0, since any reset moves it away from 0, but the
+    /// [`Wait`] futures that existed at the time complete regardless.
+    cancels: AtomicU64,
+    /// The number of alive [`ActivityGuard`]s
+    active_guards: AtomicUsize,
+    /// The latest idle deadline a waiter may be sleeping until, or 0 if nobody is sleeping until
+    /// an idle deadline
+    armed_ns: AtomicU64,
+    waiters: Arc<Mutex<Waiters>>,
+    /// Wakes every waiter up, see [`Timeout::poll_sleep_until`]
+    wake_all: Waker,
+    sleep: Mutex<SharedSleep<R::Sleep>>,
+    /// Hooks to run upon the next observed expiry
+    callbacks: Mutex<Vec<Callback>>,
+    /// The number of resets so far, wrapping around
+    resets: AtomicU64,
+    /// The number of futures waiting for the next reset
+    listeners: AtomicUsize,
+    activity: Mutex<Waiters>,
+}
+
+/// Either a borrowed or a reference-counted timeout
+pub(crate) enum CowTimeout<'a, R: Runtime> {
+    #[cfg(feature = "std")]
+    Arc(Arc<Timeout<R>>),
+    Ref(&'a Timeout<R>),
+}
+impl<'a, R: Runtime> Clone for CowTimeout<'a, R> {
+    fn clone(&self) -> Self {
+        match self {
+            #[cfg(feature = "std")]
+            Self::Arc(x) => Self::Arc(x.clone()),
+            Self::Ref(x) => Self::Ref(x),
+        }
+    }
+}
+impl<'a, R: Runtime> AsRef<Timeout<R>> for CowTimeout<'a, R> {
+    fn as_ref(&self) -> &Timeout<R> {
+        match self {
+            #[cfg(feature = "std")]
+            Self::Arc(x) => x,
+            Self::Ref(x) => x,
+        }
+    }
+}
+
+/// The reason a [`Timeout`] has expired
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ExpiryReason {
+    /// The timeout wasn't reset for long enough
+    Idle,
+    /// The [maximum lifetime](Timeout::with_max_lifetime) of the timeout has been reached
+    MaxLifetime,
+    /// [`Timeout::expire_now`] was called
+    Cancelled,
 }
 
 /// An alias for [`Timeout`] using the tokio runtime
@@ -86,124 +215,870 @@ pub type TokioTimeout = Timeout<runtime::Tokio>;
 #[cfg(feature = "tokio")]
 impl TokioTimeout {
     /// Create a new timeout that expires after `default_timeout`, creating a runtime with [`runtime::Tokio::new`]
-    ///
-    /// # Panics
-    /// Panics if `default_timeout` is longer than ~584 years
     pub fn new_tokio(default_timeout: Duration) -> Self {
-        let runtime = runtime::Tokio::new();
-        let epoch = runtime.now();
-        let default_timeout = u64::try_from(default_timeout.as_nanos()).unwrap();
-        Self {
-            runtime,
-            epoch,
-            timeout_from_epoch_ns: default_timeout.into(),
-            default_timeout: default_timeout.into(),
-        }
+        Self::new(runtime::Tokio::new(), default_timeout)
     }
 }
 
 impl<R: Runtime> Timeout<R> {
     /// Create a new timeout that expires after `default_timeout`
-    ///
-    /// # Panics
-    /// Panics if `default_timeout` is longer than ~584 years
     #[must_use]
     pub fn new(runtime: R, default_timeout: Duration) -> Self {
+        Self::with_nanos(runtime, saturating_nanos(default_timeout))
+    }
+
+    /// Create a new timeout that expires after `default_timeout`, failing if `default_timeout`
+    /// is too long instead of treating it as infinite.
+    ///
+    /// # Errors
+    /// Returns [`Error::DurationTooLong`] if `default_timeout` is longer than ~584 years
+    pub fn try_new(runtime: R, default_timeout: Duration) -> Result<Self, Error> {
+        Ok(Self::with_nanos(runtime, checked_nanos(default_timeout)?))
+    }
+
+    /// Create a builder for a timeout with more configuration options
+    pub fn builder(runtime: R) -> Builder<R> {
+        Builder::new(runtime)
+    }
+
+    fn with_nanos(runtime: R, default_timeout: u64) -> Self {
         let epoch = runtime.now();
-        let default_timeout = u64::try_from(default_timeout.as_nanos()).unwrap();
+        let waiters = Arc::new(Mutex::default());
         Self {
             runtime,
             epoch,
+            last_reset_from_epoch_ns: 0.into(),
             timeout_from_epoch_ns: default_timeout.into(),
             default_timeout: default_timeout.into(),
+            paused_remaining_ns: u64::MAX.into(),
+            initial: false.into(),
+            phases: Vec::new(),
+            phase: usize::MAX.into(),
+            max_lifetime_ns: u64::MAX.into(),
+            cancels: 0.into(),
+            active_guards: 0.into(),
+            armed_ns: 0.into(),
+            wake_all: Waker::from(waiters.clone()),
+            waiters,
+            sleep: Mutex::default(),
+            callbacks: Mutex::default(),
+            resets: 0.into(),
+            listeners: 0.into(),
+            activity: Mutex::default(),
         }
     }
 
+    /// Limit the total lifetime of the timeout. Once `max_lifetime` has passed since the timeout
+    /// was created, it expires no matter how many times it was reset, and regardless of it
+    /// being [paused](Timeout::pause) or held open by [activity guards](Timeout::activity_guard).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use async_shared_timeout::{runtime, Timeout};
+    ///
+    /// // Expire after 10 seconds of inactivity, or after an hour in any case
+    /// let timeout = Timeout::new(runtime::Tokio::new(), Duration::from_secs(10))
+    ///     .with_max_lifetime(Duration::from_secs(3600));
+    /// ```
+    #[must_use]
+    pub fn with_max_lifetime(self, max_lifetime: Duration) -> Self {
+        self.set_max_lifetime_nanos(saturating_nanos(max_lifetime));
+        self
+    }
+
+    /// Use a different timeout until the first [`reset`](Timeout::reset), after which the
+    /// default timeout takes over. This is useful to give a peer a different amount of time to
+    /// send its first bytes than to stay idle afterwards.
+    ///
+    /// When used with a `Wrapper`, the first successful read (or future completion, or stream
+    /// advancement) ends the initial phase, while writes and seeks don't reset the timeout until
+    /// then.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use async_shared_timeout::{runtime, Timeout};
+    ///
+    /// // Allow 5 seconds for the first request, and 60 seconds of inactivity afterwards
+    /// let timeout = Timeout::new(runtime::Tokio::new(), Duration::from_secs(60))
+    ///     .with_initial_timeout(Duration::from_secs(5));
+    /// ```
+    #[must_use]
+    pub fn with_initial_timeout(self, initial_timeout: Duration) -> Self {
+        self.set_initial_timeout_nanos(saturating_nanos(initial_timeout));
+        self
+    }
+
+    fn set_initial_timeout_nanos(&self, initial_timeout: u64) {
+        self.initial.store(true, Ordering::SeqCst);
+        self.set_deadline(initial_timeout);
+    }
+
+    /// Whether the [initial timeout](Timeout::with_initial_timeout) is still in effect
+    pub fn in_initial_phase(&self) -> bool {
+        self.initial.load(Ordering::SeqCst)
+    }
+
+    fn set_max_lifetime_nanos(&self, max_lifetime: u64) {
+        self.max_lifetime_ns.store(max_lifetime, Ordering::Release);
+    }
+
+    /// The maximum lifetime set with [`with_max_lifetime`](Timeout::with_max_lifetime)
+    pub fn max_lifetime(&self) -> Option<Duration> {
+        let max_lifetime = self.max_lifetime_ns.load(Ordering::Acquire);
+        (max_lifetime != u64::MAX).then(|| Duration::from_nanos(max_lifetime))
+    }
+
     fn elapsed(&self) -> Duration {
         self.runtime.now().duration_since(&self.epoch)
     }
 
+    fn elapsed_nanos(&self) -> u64 {
+        saturating_nanos(self.elapsed())
+    }
+
+    /// Move the deadline, waking the waiters up if they may be sleeping past the new deadline.
+    fn set_deadline(&self, timeout_from_epoch_ns: u64) {
+        self.timeout_from_epoch_ns
+            .store(timeout_from_epoch_ns, Ordering::SeqCst);
+        self.deadline_changed(timeout_from_epoch_ns);
+    }
+
+    /// Wake the waiters up if they may be sleeping past a newly stored deadline.
+    fn deadline_changed(&self, timeout_from_epoch_ns: u64) {
+        if timeout_from_epoch_ns < self.armed_ns.load(Ordering::SeqCst) {
+            // Every waiter raises it again when re-arming its sleep
+            self.armed_ns.store(0, Ordering::SeqCst);
+            self.wake_waiters();
+        }
+    }
+
+    /// Wake up every waiter
+    pub(crate) fn wake_waiters(&self) {
+        self.wake_all.wake_by_ref();
+    }
+
+    /// Sleep until `target` at the latest, `elapsed` being the current time. The caller must be
+    /// registered as a waiter: the sleep is shared by all of them, and it wakes every waiter up
+    /// when it fires, whoever polled it last. Once this returns [`Poll::Ready`], the caller
+    /// should check the time again and call this again if needed.
+    pub(crate) fn poll_sleep_until(&self, target: u64, elapsed: u64) -> Poll<()> {
+        let poll = self.sleep.lock().poll_until(
+            target,
+            elapsed,
+            |remaining| self.runtime.create_sleep(remaining),
+            &self.wake_all,
+        );
+        if poll.is_ready() {
+            // The sleep may have been armed by someone else
+            self.wake_waiters();
+        }
+        poll
+    }
+
+    /// The deadline a registered waiter should sleep until, `u64::MAX` meaning forever. Any
+    /// change that moves the idle deadline earlier than this wakes the waiter up.
+    pub(crate) fn arm(&self) -> u64 {
+        loop {
+            let idle_deadline = self.idle_deadline();
+            // While paused or held open by a guard, deadline changes are irrelevant: resuming
+            // and releasing the last guard wake the waiters up explicitly
+            self.armed_ns
+                .fetch_max(idle_deadline.unwrap_or(0), Ordering::SeqCst);
+            // Any deadline change racing with the update above is either seen here, or sees the
+            // new armed deadline and wakes us up
+            if self.idle_deadline() == idle_deadline {
+                return idle_deadline
+                    .unwrap_or(u64::MAX)
+                    .min(self.max_lifetime_ns.load(Ordering::Acquire));
+            }
+        }
+    }
+
+    /// The idle deadline, or `None` if the timeout is paused or held open by a guard.
+    fn idle_deadline(&self) -> Option<u64> {
+        // Guards can't hold a cancelled timeout open
+        if self.is_cancelled() {
+            return Some(0);
+        }
+        (self.paused_remaining_ns.load(Ordering::SeqCst) == u64::MAX
+            && self.active_guards.load(Ordering::SeqCst) == 0)
+            .then(|| self.timeout_from_epoch_ns.load(Ordering::SeqCst))
+    }
+
+    /// Whether [`Timeout::expire_now`] was called, and the timeout wasn't reset since then
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancels.load(Ordering::SeqCst) != 0
+            && self.timeout_from_epoch_ns.load(Ordering::SeqCst) == 0
+    }
+
+    /// The deadline waiters should sleep until, `u64::MAX` meaning forever.
+    pub(crate) fn effective_deadline(&self) -> u64 {
+        self.idle_deadline()
+            .unwrap_or(u64::MAX)
+            .min(self.max_lifetime_ns.load(Ordering::Acquire))
+    }
+
+    /// The deadline as it's seen by the user, i.e. with the pause time not counted.
+    fn projected_deadline(&self, now: u64) -> u64 {
+        let paused_remaining = self.paused_remaining_ns.load(Ordering::SeqCst);
+        let idle_deadline = if paused_remaining == u64::MAX {
+            self.timeout_from_epoch_ns.load(Ordering::SeqCst)
+        } else {
+            now.saturating_add(paused_remaining)
+        };
+        idle_deadline.min(self.max_lifetime_ns.load(Ordering::Acquire))
+    }
+
     /// Reset the timeout to the default time.
     ///
-    /// This function is cheap to call.
+    /// This function is cheap to call. If this moves the deadline earlier than the one the
+    /// waiters are currently sleeping until (e.g. after a [`set_default_timeout`] with a shorter
+    /// timeout), they are woken up immediately to re-arm their sleep.
     ///
-    /// # Panics
-    /// Panics if over ~584 years have elapsed since the timer started.
+    /// If the timeout is [paused](Timeout::pause), it will resume with the default time left.
+    ///
+    /// [`set_default_timeout`]: Timeout::set_default_timeout
     pub fn reset(&self) {
-        self.timeout_from_epoch_ns.store(
-            u64::try_from(self.elapsed().as_nanos()).unwrap()
-                + self.default_timeout.load(Ordering::Acquire),
-            Ordering::Release,
+        self.reset_for(self.elapsed_nanos(), self.default_timeout_nanos());
+    }
+
+    /// Reset the timeout to expire after `timeout` instead of the default time. Subsequent
+    /// resets will use the default time again.
+    ///
+    /// See [`reset`](Timeout::reset) for more info.
+    pub fn reset_with(&self, timeout: Duration) {
+        self.reset_for(self.elapsed_nanos(), saturating_nanos(timeout));
+    }
+
+    /// Reset the timeout to expire at `deadline`. If `deadline` is in the past, the timeout
+    /// expires right away.
+    ///
+    /// See [`reset`](Timeout::reset) for more info.
+    pub fn reset_at(&self, deadline: R::Instant) {
+        let now = self.elapsed_nanos();
+        let target = saturating_nanos(deadline.duration_since(&self.epoch));
+        self.reset_for(now, target.saturating_sub(now));
+    }
+
+    /// Reset the timeout, unless the initial timeout is still in effect
+    #[cfg(all(feature = "read-write", any(feature = "tokio", feature = "futures-io")))]
+    pub(crate) fn reset_after_initial(&self) {
+        if !self.in_initial_phase() {
+            self.reset();
+        }
+    }
+
+    fn reset_for(&self, now: u64, timeout: u64) {
+        if self.initial.load(Ordering::Relaxed) {
+            self.initial.store(false, Ordering::SeqCst);
+        }
+        self.last_reset_from_epoch_ns.store(now, Ordering::Release);
+        self.set_deadline(now.saturating_add(timeout));
+        // A concurrent pause either sees the new deadline, or is seen here
+        let _ = self.paused_remaining_ns.fetch_update(
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+            |remaining| (remaining != u64::MAX).then_some(timeout.min(u64::MAX - 1)),
         );
+        self.resets.fetch_add(1, Ordering::SeqCst);
+        // A concurrent listener either sees the new reset count, or is seen here
+        if self.listeners.load(Ordering::SeqCst) != 0 {
+            let wakers = self.activity.lock().all();
+            for waker in wakers {
+                waker.wake();
+            }
+        }
     }
 
-    /// The default timeout. Timeout will be reset to this value upon a successful operation.
-    pub fn default_timeout(&self) -> Duration {
-        Duration::from_nanos(self.default_timeout.load(Ordering::Acquire))
+    /// Move the deadline one default timeout away from now, without it counting as activity
+    #[cfg(feature = "stream")]
+    pub(crate) fn rearm(&self) {
+        let target = self
+            .elapsed_nanos()
+            .saturating_add(self.default_timeout_nanos());
+        // Moving the deadline later never requires waking the waiters up, and a deadline set by
+        // a concurrent reset is kept
+        self.timeout_from_epoch_ns
+            .fetch_max(target, Ordering::SeqCst);
     }
-    /// Change the default timeout.
+
+    /// Push the current deadline further by `duration`. Unlike [`reset`](Timeout::reset), this
+    /// isn't considered activity, so it doesn't affect [`last_reset`](Timeout::last_reset).
     ///
-    /// Warning: if this timeout is shorter than previous one, it will only update after the
-    /// previous timeout has expired!
+    /// If the timeout is [paused](Timeout::pause), the frozen time left is extended instead.
+    pub fn extend_by(&self, duration: Duration) {
+        let duration = saturating_nanos(duration);
+        // Moving the deadline later never requires waking the waiters up
+        let _ =
+            self.timeout_from_epoch_ns
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |target| {
+                    Some(target.saturating_add(duration))
+                });
+        let _ = self.paused_remaining_ns.fetch_update(
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+            |remaining| {
+                (remaining != u64::MAX)
+                    .then(|| remaining.saturating_add(duration).min(u64::MAX - 1))
+            },
+        );
+    }
+
+    /// Pause the timeout, freezing the time left until [`resume`](Timeout::resume) is called.
+    ///
+    /// While paused, the timeout can't expire and [`wait`](Timeout::wait) futures stay pending.
+    /// Has no effect if the timeout is already paused or has already expired.
+    pub fn pause(&self) {
+        // `u64::MAX` means "not paused", the time left is clamped to never be equal to it
+        let remaining_at = |target: u64| {
+            target
+                .saturating_sub(self.elapsed_nanos())
+                .min(u64::MAX - 1)
+        };
+        let mut target = self.timeout_from_epoch_ns.load(Ordering::SeqCst);
+        let mut remaining = remaining_at(target);
+        if remaining == 0
+            || self
+                .paused_remaining_ns
+                .compare_exchange(u64::MAX, remaining, Ordering::SeqCst, Ordering::SeqCst)
+                .is_err()
+        {
+            return;
+        }
+        // The deadline may have been moved by a reset that didn't see us pausing
+        loop {
+            let new_target = self.timeout_from_epoch_ns.load(Ordering::SeqCst);
+            if new_target == target {
+                break;
+            }
+            let new_remaining = remaining_at(new_target);
+            if self
+                .paused_remaining_ns
+                .compare_exchange(remaining, new_remaining, Ordering::SeqCst, Ordering::SeqCst)
+                .is_err()
+            {
+                break;
+            }
+            target = new_target;
+            remaining = new_remaining;
+        }
+    }
+
+    /// Resume a [paused](Timeout::pause) timeout, which will then expire after the time that
+    /// was left when it was paused. Has no effect if the timeout isn't paused.
+    pub fn resume(&self) {
+        let remaining = self.paused_remaining_ns.swap(u64::MAX, Ordering::SeqCst);
+        if remaining != u64::MAX {
+            // The stored deadline is never later than this, unless a reset raced with us
+            self.timeout_from_epoch_ns.fetch_max(
+                self.elapsed_nanos().saturating_add(remaining),
+                Ordering::SeqCst,
+            );
+            self.wake_waiters();
+        }
+    }
+
+    /// Whether the timeout is [paused](Timeout::pause).
+    pub fn is_paused(&self) -> bool {
+        self.paused_remaining_ns.load(Ordering::SeqCst) != u64::MAX
+    }
+
+    /// Hold the timeout open until the returned guard is dropped.
+    ///
+    /// While at least one guard is alive, the timeout can't expire, unless
+    /// [`expire_now`](Timeout::expire_now) is called. Dropping the last guard
+    /// [resets](Timeout::reset) the timeout, unless it was expired with `expire_now`. This is
+    /// useful for operations that may take an unbounded amount of time, but shouldn't count as
+    /// inactivity.
+    ///
+    /// To move the guard into a spawned task, use [`SharedTimeout::activity_guard`].
+    ///
+    /// # Example
     ///
-    /// Additionally, this won't automatically reset the timeout - it will only affect the next
+    /// ```
+    /// # async fn handle_request() {}
+    /// # async fn example_fn() {
+    /// use std::time::Duration;
+    ///
+    /// let timeout = async_shared_timeout::TokioTimeout::new_tokio(Duration::from_secs(10));
+    /// {
+    ///     let _guard = timeout.activity_guard();
+    ///     handle_request().await;
+    /// }
+    /// # }
+    /// ```
+    pub fn activity_guard(&self) -> ActivityGuard<'_, R> {
+        ActivityGuard::new(CowTimeout::Ref(self))
+    }
+
+    /// Make the timeout expire right now, completing every [`wait`](Timeout::wait) future. This
+    /// takes priority over [pausing](Timeout::pause) and [activity guards](Timeout::activity_guard).
+    ///
+    /// Just like with a regular expiry, the timeout can be brought back to life with a
+    /// [`reset`](Timeout::reset) afterwards. The waiters that existed at the time of the call
+    /// still complete with [`ExpiryReason::Cancelled`], even if they're only polled after the
     /// reset.
+    pub fn expire_now(&self) {
+        self.cancels.fetch_add(1, Ordering::SeqCst);
+        self.paused_remaining_ns.store(u64::MAX, Ordering::SeqCst);
+        self.set_deadline(0);
+        // A concurrent reset may have brought the timeout back to life already
+        if let Some(reason) = self.expiry_reason() {
+            self.run_callbacks(reason);
+        }
+        self.wake_waiters();
+    }
+
+    /// Register a hook to be called once the timeout expires, with the reason of the expiry and
+    /// the [idle time](Timeout::idle_for) at that moment.
     ///
-    /// # Panics
-    /// Panics if `default_timeout` is longer than ~584 years
-    pub fn set_default_timeout(&self, default_timeout: Duration) {
-        self.default_timeout.store(
-            u64::try_from(default_timeout.as_nanos()).unwrap(),
-            Ordering::Release,
-        );
+    /// Every hook is called exactly once, by whoever observes the expiry first: a future
+    /// [waiting](Timeout::wait) for the timeout, or [`Timeout::expire_now`]. The timeout doesn't
+    /// drive itself, so unless something else waits for it, spawn
+    /// [`wait_owned`](Timeout::wait_owned) on your runtime of choice, or use
+    /// [`SharedTimeout::spawn_on_expire`] with tokio. Hooks registered after an expiry are
+    /// called upon the next observed expiry, i.e. right away if the timeout is still expired.
+    ///
+    /// Hooks run synchronously in the task that observed the expiry, so they should be short.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example_fn() {
+    /// use std::time::Duration;
+    /// use async_shared_timeout::TokioTimeout;
+    ///
+    /// let timeout = TokioTimeout::new_tokio(Duration::from_millis(10));
+    /// timeout.on_expire(|reason, idle| println!("timed out ({reason:?}) after {idle:?}"));
+    /// timeout.wait().await;
+    /// # }
+    /// ```
+    pub fn on_expire(&self, hook: impl FnOnce(ExpiryReason, Duration) + Send + 'static) {
+        self.callbacks.lock().push(Box::new(hook));
     }
 
-    fn timeout_duration(&self) -> Option<Duration> {
-        let elapsed_nanos = u64::try_from(self.elapsed().as_nanos()).unwrap();
-        let target_nanos = self.timeout_from_epoch_ns.load(Ordering::Acquire);
-        (elapsed_nanos < target_nanos).then(|| Duration::from_nanos(target_nanos - elapsed_nanos))
+    /// Call the registered expiry hooks
+    pub(crate) fn run_callbacks(&self, reason: ExpiryReason) {
+        let callbacks = core::mem::take(&mut *self.callbacks.lock());
+        if callbacks.is_empty() {
+            return;
+        }
+        let idle = self.idle_for();
+        for callback in callbacks {
+            callback(reason, idle);
+        }
     }
 
-    /// Wait for the timeout to expire
+    /// Whether the timeout has expired. A timeout can't expire while it's [paused](Timeout::pause)
+    /// or while [activity guards](Timeout::activity_guard) are held, unless
+    /// [`expire_now`](Timeout::expire_now) is called.
+    pub fn is_expired(&self) -> bool {
+        self.expiry_reason().is_some()
+    }
+
+    /// Why the timeout has expired, or `None` if it hasn't.
     ///
-    /// This is a function that's expensive to start, so for best performance, only call it once
-    /// per timer - launch it separately and call [`reset`](Timeout::reset) from the
-    /// other futures (see the example in top-level documentation).
-    pub async fn wait(&self) {
-        pin_project_lite::pin_project! {
-            struct SleepFuture<F: Sleep> {
-                #[pin]
-                inner: F,
-            }
+    /// If several reasons apply, [`ExpiryReason::Cancelled`] takes priority over
+    /// [`ExpiryReason::MaxLifetime`], which takes priority over [`ExpiryReason::Idle`].
+    pub fn expiry_reason(&self) -> Option<ExpiryReason> {
+        let now = self.elapsed_nanos();
+        (now >= self.effective_deadline()).then(|| self.reason_at(now))
+    }
+
+    /// The reason of an expiry that has happened before `now`
+    fn reason_at(&self, now: u64) -> ExpiryReason {
+        if self.is_cancelled() {
+            ExpiryReason::Cancelled
+        } else if now >= self.max_lifetime_ns.load(Ordering::Acquire) {
+            ExpiryReason::MaxLifetime
+        } else {
+            ExpiryReason::Idle
         }
+    }
 
-        impl<F: Sleep> Future for SleepFuture<F> {
-            type Output = ();
-            fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-                self.project().inner.poll_sleep(cx)
-            }
+    /// Time left until the timeout expires, or `None` if it has already expired.
+    ///
+    /// While [paused](Timeout::pause), this is the frozen time left (unless the
+    /// [maximum lifetime](Timeout::with_max_lifetime) is reached sooner).
+    pub fn remaining(&self) -> Option<Duration> {
+        let elapsed = self.elapsed_nanos();
+        let target = self.projected_deadline(elapsed);
+        (elapsed < target).then(|| Duration::from_nanos(target - elapsed))
+    }
+
+    /// The point in time at which the timeout expires (or has expired).
+    ///
+    /// While [paused](Timeout::pause), this is the deadline the timeout would have if it was
+    /// resumed right now.
+    ///
+    /// # Panics
+    /// Panics if the deadline can't be represented by the runtime's instant type.
+    pub fn deadline(&self) -> R::Instant {
+        self.instant_from_epoch(self.projected_deadline(self.elapsed_nanos()))
+    }
+
+    /// The point in time of the last [`reset`](Timeout::reset), or of the timeout creation if it
+    /// was never reset.
+    ///
+    /// # Panics
+    /// Panics if the instant can't be represented by the runtime's instant type.
+    pub fn last_reset(&self) -> R::Instant {
+        self.instant_from_epoch(self.last_reset_from_epoch_ns.load(Ordering::Acquire))
+    }
+
+    /// Time elapsed since the last [`reset`](Timeout::reset), or since the timeout creation if
+    /// it was never reset.
+    pub fn idle_for(&self) -> Duration {
+        let last_reset = self.last_reset_from_epoch_ns.load(Ordering::Acquire);
+        Duration::from_nanos(self.elapsed_nanos().saturating_sub(last_reset))
+    }
+
+    fn instant_from_epoch(&self, nanos: u64) -> R::Instant {
+        self.epoch
+            .checked_add(Duration::from_nanos(nanos))
+            .expect("instant out of range")
+    }
+
+    /// The default timeout. Timeout will be reset to this value upon a successful operation.
+    pub fn default_timeout(&self) -> Duration {
+        Duration::from_nanos(self.default_timeout_nanos())
+    }
+    pub(crate) fn default_timeout_nanos(&self) -> u64 {
+        match self.phases.get(self.phase.load(Ordering::Acquire)) {
+            Some((_, default_timeout)) => *default_timeout,
+            None => self.default_timeout.load(Ordering::Acquire),
         }
-        if let Some(timeout) = self.timeout_duration() {
-            let future = self.runtime.create_sleep(timeout);
-            let mut future = SleepFuture { inner: future };
-            // SAFETY: the original future binding is shadowed,
-            // so the unpinned binding can never be accessed again.
-            // This is exactly the same code as the tokio::pin! macro
-            let future = &mut unsafe { Pin::new_unchecked(&mut future) };
-            while let Some(instant) = self.timeout_duration() {
-                future.as_mut().project().inner.reset(instant);
-                future.as_mut().await;
-            }
+    }
+    /// Set the default timeout, leaving the current phase
+    fn store_default_timeout(&self, default_timeout: u64) {
+        self.default_timeout
+            .store(default_timeout, Ordering::Release);
+        self.phase.store(usize::MAX, Ordering::Release);
+    }
+    /// Change the default timeout.
+    ///
+    /// This won't automatically reset the timeout - it will only affect the next reset. If the
+    /// new timeout is shorter than the previous one, the next reset will wake the waiters so that
+    /// the shorter deadline is respected. Use
+    /// [`update_default_timeout`](Timeout::update_default_timeout) to apply it right away.
+    ///
+    /// This leaves the current [phase](Timeout::set_phase), if any.
+    pub fn set_default_timeout(&self, default_timeout: Duration) {
+        self.store_default_timeout(saturating_nanos(default_timeout));
+    }
+    /// Change the default timeout, failing if `default_timeout` is too long instead of treating
+    /// it as infinite. See [`set_default_timeout`](Timeout::set_default_timeout) for more info.
+    ///
+    /// # Errors
+    /// Returns [`Error::DurationTooLong`] if `default_timeout` is longer than ~584 years
+    pub fn try_set_default_timeout(&self, default_timeout: Duration) -> Result<(), Error> {
+        self.store_default_timeout(checked_nanos(default_timeout)?);
+        Ok(())
+    }
+    /// Switch to a phase configured with [`Builder::phase`], making its timeout the default
+    /// timeout. Like with [`set_default_timeout`](Timeout::set_default_timeout), this doesn't
+    /// affect the current deadline until the next reset.
+    ///
+    /// # Errors
+    /// Returns [`Error::UnknownPhase`] if the phase wasn't configured.
+    pub fn set_phase(&self, phase: &str) -> Result<(), Error> {
+        let index = self
+            .phases
+            .iter()
+            .position(|(name, _)| *name == phase)
+            .ok_or(Error::UnknownPhase)?;
+        self.phase.store(index, Ordering::Release);
+        Ok(())
+    }
+    /// Switch to a phase configured with [`Builder::phase`] and [reset](Timeout::reset) the
+    /// timeout with its timeout.
+    ///
+    /// # Errors
+    /// Returns [`Error::UnknownPhase`] if the phase wasn't configured.
+    pub fn set_phase_and_reset(&self, phase: &str) -> Result<(), Error> {
+        self.set_phase(phase)?;
+        self.reset();
+        Ok(())
+    }
+    /// The name of the current phase, or `None` if no phase was set, or if the default timeout
+    /// was changed since then
+    pub fn phase(&self) -> Option<&'static str> {
+        let index = self.phase.load(Ordering::Acquire);
+        self.phases.get(index).map(|(name, _)| *name)
+    }
+    /// Change the default timeout and apply it to the current deadline right away, as if the
+    /// last [`reset`](Timeout::reset) was done with the new default timeout.
+    ///
+    /// If this moves the deadline earlier, the waiters are woken up immediately, and the timeout
+    /// may expire right away. Deadlines set with [`reset_with`](Timeout::reset_with),
+    /// [`reset_at`](Timeout::reset_at) or [`extend_by`](Timeout::extend_by) are overridden,
+    /// but a timeout that has already expired stays expired, and the
+    /// [initial timeout](Timeout::with_initial_timeout) stays in effect until the first reset.
+    ///
+    /// If the timeout is [paused](Timeout::pause), the frozen time left moves along with the
+    /// deadline. This leaves the current [phase](Timeout::set_phase), if any.
+    pub fn update_default_timeout(&self, default_timeout: Duration) {
+        let default_timeout = saturating_nanos(default_timeout);
+        self.store_default_timeout(default_timeout);
+        if self.in_initial_phase() {
+            return;
         }
+        let target = self
+            .last_reset_from_epoch_ns
+            .load(Ordering::Acquire)
+            .saturating_add(default_timeout);
+        let now = self.elapsed_nanos();
+        // A paused timeout hasn't expired, even if its stored deadline has passed
+        let updated = self.timeout_from_epoch_ns.fetch_update(
+            Ordering::SeqCst,
+            Ordering::SeqCst,
+            |current| (current > now || self.is_paused()).then_some(target),
+        );
+        if let Ok(previous) = updated {
+            // The frozen time left moves by as much as the deadline did
+            let _ = self.paused_remaining_ns.fetch_update(
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+                |remaining| {
+                    (remaining != u64::MAX).then(|| {
+                        if target < previous {
+                            remaining.saturating_sub(previous - target)
+                        } else {
+                            remaining
+                                .saturating_add(target - previous)
+                                .min(u64::MAX - 1)
+                        }
+                    })
+                },
+            );
+            self.deadline_changed(target);
+        }
+    }
+
+    /// Wait for the timeout to expire
+    ///
+    /// The waiters only wake up when the deadline is reached or when it's moved to an earlier
+    /// point in time, so it's fine to call [`reset`](Timeout::reset) as often as needed.
+    ///
+    /// Any number of tasks may wait on the same timeout concurrently: they share a single sleep
+    /// with the runtime, driven by whichever of them is polled, so a waiter that's never polled
+    /// again doesn't hold the others back.
+    pub async fn wait(&self) {
+        self.wait_with_reason().await;
+    }
+
+    /// Wait for the timeout to expire, returning the reason of the expiry.
+    ///
+    /// Unlike [`wait`](Timeout::wait), this returns a nameable [`Wait`] future. Awaiting a
+    /// `&Timeout` directly does the same. See [`wait`](Timeout::wait) for more info.
+    pub fn wait_with_reason(&self) -> Wait<'_, R> {
+        Wait::new(CowTimeout::Ref(self))
+    }
+
+    /// Wait for the timeout to expire, keeping it alive with an `Arc` pointer rather than
+    /// borrowing it. The returned future is `'static`, so it can be spawned as a separate task.
+    /// See [`wait_with_reason`](Timeout::wait_with_reason) for more info.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example_fn() {
+    /// use std::{sync::Arc, time::Duration};
+    /// use async_shared_timeout::TokioTimeout;
+    ///
+    /// let timeout = Arc::new(TokioTimeout::new_tokio(Duration::from_secs(10)));
+    /// let watcher = tokio::spawn(timeout.clone().wait_owned());
+    /// timeout.expire_now();
+    /// watcher.await.unwrap();
+    /// # }
+    /// ```
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn wait_owned(self: Arc<Self>) -> Wait<'static, R> {
+        Wait::new(CowTimeout::Arc(self))
+    }
+
+    /// Wait until the timeout is about to expire, i.e. until there's `lead` time left. Completes
+    /// right away if there's less than `lead` time left already.
+    ///
+    /// This can be used to warn the peer, or to send a keepalive probe, before the timeout
+    /// actually expires. If the timeout is reset after the warning, the expiry is postponed as
+    /// usual.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn send_notice() {}
+    /// # async fn example_fn() {
+    /// use std::time::Duration;
+    /// use async_shared_timeout::TokioTimeout;
+    ///
+    /// let timeout = TokioTimeout::new_tokio(Duration::from_millis(100));
+    /// timeout.wait_warning(Duration::from_millis(50)).await;
+    /// send_notice().await;
+    /// timeout.wait().await;
+    /// # }
+    /// ```
+    pub fn wait_warning(&self, lead: Duration) -> Warning<'_, R> {
+        Warning::new(CowTimeout::Ref(self), saturating_nanos(lead))
+    }
+
+    /// A stream that yields every time the timeout expires, re-arming it afterwards. See
+    /// [`Watchdog`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example_fn() {
+    /// use std::time::Duration;
+    /// use async_shared_timeout::TokioTimeout;
+    /// use futures_util::StreamExt;
+    ///
+    /// let timeout = TokioTimeout::new_tokio(Duration::from_millis(10));
+    /// let mut watchdog = timeout.watchdog();
+    /// while let Some(expiry) = watchdog.next().await {
+    ///     println!("stalled ({} periods missed)", expiry.missed());
+    ///     # break;
+    /// }
+    /// # }
+    /// ```
+    #[cfg(feature = "stream")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+    pub fn watchdog(&self) -> Watchdog<'_, R> {
+        Watchdog::new(CowTimeout::Ref(self))
+    }
+
+    /// A stream that yields whenever the timeout hasn't been reset for `interval`, e.g. to only
+    /// send keepalive pings on quiet connections. See [`Heartbeats`].
+    ///
+    /// # Panics
+    /// Panics if `interval` is zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn send_ping() {}
+    /// # async fn example_fn() {
+    /// use std::time::Duration;
+    /// use async_shared_timeout::TokioTimeout;
+    /// use futures_util::StreamExt;
+    ///
+    /// let timeout = TokioTimeout::new_tokio(Duration::from_millis(30));
+    /// let mut heartbeats = timeout.heartbeats(Duration::from_millis(10));
+    /// while let Some(idle) = heartbeats.next().await {
+    ///     println!("idle for {idle:?}, sending a ping");
+    ///     send_ping().await;
+    /// }
+    /// # }
+    /// ```
+    #[cfg(feature = "stream")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+    pub fn heartbeats(&self, interval: Duration) -> Heartbeats<'_, R> {
+        assert!(!interval.is_zero(), "heartbeat interval must be non-zero");
+        Heartbeats::new(CowTimeout::Ref(self), saturating_nanos(interval))
+    }
+
+    /// Wait until the timeout is [reset](Timeout::reset) next time, i.e. until there's activity.
+    /// This is the opposite of [`wait`](Timeout::wait), and can be used to notice a connection
+    /// that becomes active again.
+    ///
+    /// Only resets that happen after this is called count. [`extend_by`](Timeout::extend_by)
+    /// isn't considered activity.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example_fn() {
+    /// use std::time::Duration;
+    /// use async_shared_timeout::TokioTimeout;
+    ///
+    /// let timeout = TokioTimeout::new_tokio(Duration::from_secs(10));
+    /// tokio::join!(timeout.wait_for_activity(), async {
+    ///     timeout.reset();
+    /// });
+    /// # }
+    /// ```
+    pub fn wait_for_activity(&self) -> Activity<'_, R> {
+        Activity::new(CowTimeout::Ref(self))
+    }
+
+    /// A stream that yields every time the timeout is [reset](Timeout::reset). See
+    /// [`ActivityStream`] and [`wait_for_activity`](Timeout::wait_for_activity).
+    #[cfg(feature = "stream")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+    pub fn activity(&self) -> ActivityStream<'_, R> {
+        ActivityStream::new(CowTimeout::Ref(self))
+    }
+
+    /// A stream of the time left until the timeout expires, yielded every `tick` and after
+    /// every reset, e.g. for a "session expires in" indicator. See [`Countdown`].
+    ///
+    /// # Panics
+    /// Panics if `tick` is zero.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example_fn() {
+    /// use std::time::Duration;
+    /// use async_shared_timeout::TokioTimeout;
+    /// use futures_util::StreamExt;
+    ///
+    /// let timeout = TokioTimeout::new_tokio(Duration::from_millis(30));
+    /// let mut countdown = timeout.countdown(Duration::from_millis(10));
+    /// while let Some(remaining) = countdown.next().await {
+    ///     println!("session expires in {}ms", remaining.as_millis());
+    /// }
+    /// # }
+    /// ```
+    #[cfg(feature = "stream")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+    pub fn countdown(&self, tick: Duration) -> Countdown<'_, R> {
+        assert!(!tick.is_zero(), "countdown tick must be non-zero");
+        Countdown::new(CowTimeout::Ref(self), saturating_nanos(tick))
+    }
+
+    /// Run a future until it completes or the timeout expires, whichever happens first.
+    ///
+    /// This doesn't reset the timeout by itself, wrap the future in a `Wrapper` (or use
+    /// `Wrapper::run`) for that.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn read_command() -> Option<&'static str> { Some("command") }
+    /// # async fn example_fn() -> std::io::Result<()> {
+    /// use std::time::Duration;
+    /// use async_shared_timeout::TokioTimeout;
+    ///
+    /// let timeout = TokioTimeout::new_tokio(Duration::from_secs(10));
+    /// timeout
+    ///     .run(async {
+    ///         while let Some(cmd) = read_command().await {
+    ///             println!("command received: {:?}", cmd);
+    ///             timeout.reset();
+    /// #           break;
+    ///         }
+    ///     })
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn run<F: Future>(&self, future: F) -> Run<'_, R, F> {
+        Run::new(self.wait_with_reason(), future)
     }
 }
 
 #[cfg(feature = "wrapper")]
 mod wrapper;
-#[cfg(feature = "wrapper")]
-pub use wrapper::Wrapper;
 #[cfg(all(feature = "wrapper", feature = "tokio"))]
 pub use wrapper::TokioWrapper;
+#[cfg(feature = "wrapper")]
+pub use wrapper::Wrapper;
 
 #[cfg(test)]
 mod tests {
@@ -239,4 +1114,695 @@ mod tests {
         }));
         assert!(start.elapsed() >= Duration::from_secs(2));
     }
+    #[test]
+    fn test_shortened_deadline() {
+        let start = Instant::now();
+        tokio_test::block_on(async {
+            let timer = Timeout::new(runtime::Tokio::new(), Duration::from_secs(10));
+            tokio::join!(timer.wait(), async {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                timer.set_default_timeout(Duration::from_millis(100));
+                timer.reset();
+            });
+        });
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+    #[test]
+    fn test_many_waiters() {
+        let start = Instant::now();
+        tokio_test::block_on(async {
+            let timer = Timeout::new(runtime::Tokio::new(), Duration::from_millis(200));
+            // Waiters dropped early don't affect the rest
+            tokio::select! {
+                _ = timer.wait() => unreachable!(),
+                _ = tokio::time::sleep(Duration::from_millis(50)) => {}
+            }
+            tokio::join!(timer.wait(), timer.wait(), timer.wait(), async {
+                tokio::select! {
+                    _ = timer.wait() => unreachable!(),
+                    _ = tokio::time::sleep(Duration::from_millis(50)) => {}
+                }
+            });
+        });
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+    #[test]
+    fn test_single_sleep() {
+        static SLEEPS: AtomicUsize = AtomicUsize::new(0);
+        // Counts the sleeps it creates
+        struct Counting;
+        impl runtime::Runtime for Counting {
+            type Sleep = tokio::time::Sleep;
+            type Instant = Instant;
+            fn create_sleep(&self, timeout: Duration) -> Self::Sleep {
+                SLEEPS.fetch_add(1, Ordering::SeqCst);
+                runtime::Tokio::new().create_sleep(timeout)
+            }
+            fn now(&self) -> Self::Instant {
+                Instant::now()
+            }
+        }
+        tokio_test::block_on(async {
+            let timer = Timeout::new(Counting, Duration::from_millis(50));
+            tokio::join!(
+                timer.wait(),
+                timer.wait(),
+                timer.wait(),
+                timer.wait_warning(Duration::from_millis(20)),
+                async {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    timer.reset();
+                }
+            );
+        });
+        assert_eq!(SLEEPS.load(Ordering::SeqCst), 1);
+    }
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_stalled_waiter() {
+        use core::task::Poll;
+        let start = Instant::now();
+        tokio_test::block_on(async {
+            let timer = Arc::new(Timeout::new(
+                runtime::Tokio::new(),
+                Duration::from_millis(20),
+            ));
+            // Poll a waiter once, then keep it around without polling it again
+            let mut stalled = timer.clone().wait_owned();
+            let poll = core::future::poll_fn(|cx| Poll::Ready(stalled.poll_expired(cx))).await;
+            assert!(poll.is_pending());
+            let reason = tokio::spawn(timer.clone().wait_owned()).await.unwrap();
+            assert_eq!(reason, ExpiryReason::Idle);
+            drop(stalled);
+        });
+        assert!(start.elapsed() >= Duration::from_millis(20));
+        assert!(start.elapsed() < Duration::from_millis(200));
+    }
+    #[test]
+    fn test_expire_now() {
+        let start = Instant::now();
+        tokio_test::block_on(async {
+            let timer = Timeout::new(runtime::Tokio::new(), Duration::from_secs(10));
+            assert!(!timer.is_expired());
+            tokio::join!(timer.wait(), timer.wait(), async {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                timer.expire_now();
+            });
+            assert!(timer.is_expired());
+            timer.reset();
+            assert!(!timer.is_expired());
+        });
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+    #[test]
+    fn test_expire_now_then_reset() {
+        use core::task::Poll;
+        tokio_test::block_on(async {
+            let timer = Timeout::new(runtime::Tokio::new(), Duration::from_secs(10));
+            let mut wait = timer.wait_with_reason();
+            let poll = core::future::poll_fn(|cx| Poll::Ready(wait.poll_expired(cx))).await;
+            assert!(poll.is_pending());
+            // The waiter isn't polled before the timeout is brought back to life
+            timer.expire_now();
+            timer.reset();
+            assert!(!timer.is_expired());
+            assert_eq!(wait.await, ExpiryReason::Cancelled);
+            // New waiters wait for the new deadline
+            tokio::select! {
+                _ = timer.wait() => unreachable!(),
+                _ = tokio::time::sleep(Duration::from_millis(50)) => {}
+            }
+        });
+    }
+    #[test]
+    fn test_queries() {
+        tokio_test::block_on(async {
+            let timer = Timeout::new(runtime::Tokio::new(), Duration::from_secs(1));
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            let remaining = timer.remaining().unwrap();
+            assert!(remaining <= Duration::from_millis(900));
+            assert!(timer.idle_for() >= Duration::from_millis(100));
+            assert_eq!(
+                timer.deadline() - timer.last_reset(),
+                Duration::from_secs(1)
+            );
+            timer.reset();
+            assert!(timer.remaining().unwrap() > remaining);
+            assert!(timer.idle_for() < Duration::from_millis(100));
+            assert_eq!(
+                timer.deadline() - timer.last_reset(),
+                Duration::from_secs(1)
+            );
+            timer.expire_now();
+            assert_eq!(timer.remaining(), None);
+        });
+    }
+    #[test]
+    fn test_pause() {
+        let start = Instant::now();
+        tokio_test::block_on(async {
+            let timer = Timeout::new(runtime::Tokio::new(), Duration::from_millis(200));
+            tokio::join!(timer.wait(), async {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                timer.pause();
+                assert!(timer.is_paused());
+                let remaining = timer.remaining().unwrap();
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                assert_eq!(timer.remaining(), Some(remaining));
+                assert!(!timer.is_expired());
+                timer.resume();
+                assert!(!timer.is_paused());
+            });
+        });
+        assert!(start.elapsed() >= Duration::from_millis(500));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+    #[test]
+    fn test_activity_guard() {
+        let start = Instant::now();
+        tokio_test::block_on(async {
+            let timer = Timeout::new(runtime::Tokio::new(), Duration::from_millis(100));
+            tokio::join!(timer.wait(), async {
+                let guard = timer.activity_guard();
+                let guard2 = timer.activity_guard();
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                assert!(!timer.is_expired());
+                drop(guard);
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                assert!(!timer.is_expired());
+                drop(guard2);
+            });
+        });
+        assert!(start.elapsed() >= Duration::from_millis(500));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+    #[test]
+    fn test_expire_now_with_guard() {
+        tokio_test::block_on(async {
+            let timer = Timeout::new(runtime::Tokio::new(), Duration::from_secs(10));
+            let guard = timer.activity_guard();
+            let (reason, _) = tokio::join!(timer.wait_with_reason(), async {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                timer.expire_now();
+            });
+            assert_eq!(reason, ExpiryReason::Cancelled);
+            assert!(timer.is_expired());
+            drop(guard);
+            assert_eq!(timer.expiry_reason(), Some(ExpiryReason::Cancelled));
+        });
+    }
+    #[test]
+    fn test_custom_reset() {
+        let start = Instant::now();
+        tokio_test::block_on(async {
+            let timer = Timeout::new(runtime::Tokio::new(), Duration::from_secs(10));
+            timer.reset_with(Duration::from_millis(100));
+            timer.extend_by(Duration::from_millis(100));
+            timer.wait().await;
+            assert!(start.elapsed() >= Duration::from_millis(200));
+            timer.reset_at(Instant::now() + Duration::from_millis(100));
+            assert!(timer.remaining().unwrap() <= Duration::from_millis(100));
+            timer.wait().await;
+            timer.reset();
+            assert!(timer.remaining().unwrap() > Duration::from_secs(9));
+        });
+        assert!(start.elapsed() >= Duration::from_millis(300));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+    #[test]
+    fn test_update_default_timeout() {
+        let start = Instant::now();
+        tokio_test::block_on(async {
+            let timer = Timeout::new(runtime::Tokio::new(), Duration::from_secs(10));
+            tokio::join!(timer.wait(), async {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                timer.update_default_timeout(Duration::from_millis(200));
+            });
+            assert!(start.elapsed() >= Duration::from_millis(200));
+            timer.update_default_timeout(Duration::from_secs(10));
+            assert!(timer.is_expired());
+        });
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+    #[test]
+    fn test_update_default_timeout_paused() {
+        let start = Instant::now();
+        tokio_test::block_on(async {
+            let timer = Timeout::new(runtime::Tokio::new(), Duration::from_secs(10));
+            timer.pause();
+            timer.update_default_timeout(Duration::from_millis(100));
+            assert!(timer.remaining().unwrap() <= Duration::from_millis(100));
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            assert!(!timer.is_expired());
+            timer.resume();
+            assert!(timer.remaining().unwrap() <= Duration::from_millis(100));
+            timer.wait().await;
+        });
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        assert!(start.elapsed() < Duration::from_millis(500));
+    }
+    #[test]
+    fn test_max_lifetime() {
+        let start = Instant::now();
+        tokio_test::block_on(async {
+            let timer = Timeout::new(runtime::Tokio::new(), Duration::from_millis(100))
+                .with_max_lifetime(Duration::from_millis(300));
+            assert_eq!(timer.max_lifetime(), Some(Duration::from_millis(300)));
+            tokio::select! {
+                _ = timer.wait() => {}
+                _ = async {
+                    let _guard = timer.activity_guard();
+                    loop {
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        timer.reset();
+                    }
+                } => {}
+            }
+            assert_eq!(timer.expiry_reason(), Some(ExpiryReason::MaxLifetime));
+            timer.reset();
+            assert!(timer.is_expired());
+        });
+        assert!(start.elapsed() >= Duration::from_millis(300));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+    #[test]
+    fn test_expiry_reason() {
+        tokio_test::block_on(async {
+            let timer = Timeout::new(runtime::Tokio::new(), Duration::from_millis(100));
+            assert_eq!(timer.expiry_reason(), None);
+            assert_eq!(timer.wait_with_reason().await, ExpiryReason::Idle);
+            assert_eq!(timer.expiry_reason(), Some(ExpiryReason::Idle));
+            timer.reset();
+            let (reason, ()) = tokio::join!(timer.wait_with_reason(), async {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                timer.expire_now();
+            });
+            assert_eq!(reason, ExpiryReason::Cancelled);
+            timer.reset();
+            assert_eq!((&timer).await, ExpiryReason::Idle);
+        });
+    }
+    #[test]
+    fn test_infinite_timeout() {
+        let start = Instant::now();
+        tokio_test::block_on(async {
+            assert_eq!(
+                Timeout::try_new(runtime::Tokio::new(), Duration::MAX).err(),
+                Some(Error::DurationTooLong)
+            );
+            let timer = Timeout::new(runtime::Tokio::new(), Duration::MAX);
+            timer.reset();
+            timer.extend_by(Duration::MAX);
+            timer.pause();
+            timer.reset();
+            assert!(timer.is_paused());
+            timer.resume();
+            assert!(!timer.is_expired());
+            tokio::join!(timer.wait(), async {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                assert_eq!(
+                    timer.try_set_default_timeout(Duration::MAX),
+                    Err(Error::DurationTooLong)
+                );
+                timer.reset_with(Duration::from_millis(100));
+            });
+        });
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_wait_owned() {
+        let start = Instant::now();
+        tokio_test::block_on(async {
+            let timer = Arc::new(Timeout::new(
+                runtime::Tokio::new(),
+                Duration::from_millis(100),
+            ));
+            let waiter = tokio::spawn(timer.clone().wait_owned());
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            timer.reset();
+            assert_eq!(waiter.await.unwrap(), ExpiryReason::Idle);
+        });
+        assert!(start.elapsed() >= Duration::from_millis(150));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+    #[test]
+    fn test_run() {
+        tokio_test::block_on(async {
+            let timer = Timeout::new(runtime::Tokio::new(), Duration::from_millis(100));
+            assert_eq!(timer.run(async { 1 }).await, Ok(1));
+            let err = timer
+                .run(tokio::time::sleep(Duration::from_secs(1)))
+                .await
+                .unwrap_err();
+            assert_eq!(err.reason(), ExpiryReason::Idle);
+            #[cfg(feature = "std")]
+            assert_eq!(
+                std::io::Error::from(err).kind(),
+                std::io::ErrorKind::TimedOut
+            );
+        });
+    }
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_shared_timeout() {
+        let start = Instant::now();
+        tokio_test::block_on(async {
+            let timer = SharedTimeout::new(runtime::Tokio::new(), Duration::from_millis(100));
+            let handle = timer.handle();
+            let waiter = tokio::spawn(timer.clone().wait_owned());
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                handle.reset();
+            });
+            assert_eq!(waiter.await.unwrap(), ExpiryReason::Idle);
+            assert!(timer.is_expired());
+        });
+        assert!(start.elapsed() >= Duration::from_millis(150));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_shared_activity_guard() {
+        let start = Instant::now();
+        tokio_test::block_on(async {
+            let timer = SharedTimeout::new(runtime::Tokio::new(), Duration::from_millis(100));
+            let guard = timer.activity_guard();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                drop(guard);
+            });
+            assert_eq!(timer.wait_owned().await, ExpiryReason::Idle);
+        });
+        assert!(start.elapsed() >= Duration::from_millis(300));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_weak_timeout() {
+        let timer = SharedTimeout::new(runtime::Tokio::new(), Duration::from_secs(1));
+        let weak = timer.downgrade();
+        let handle = weak.upgrade().unwrap();
+        assert!(weak.reset());
+        drop(timer);
+        assert!(weak.reset_with(Duration::from_secs(2)));
+        drop(handle);
+        assert!(weak.is_dropped());
+        assert!(!weak.reset());
+        assert!(weak.upgrade().is_none());
+    }
+    #[test]
+    fn test_builder() {
+        let builder = Timeout::builder(runtime::Tokio::new());
+        assert_eq!(
+            builder.clone().build().err(),
+            Some(Error::MissingDefaultTimeout)
+        );
+        assert_eq!(
+            builder
+                .clone()
+                .default_timeout(Duration::from_secs(1))
+                .max_lifetime(Duration::MAX)
+                .build()
+                .err(),
+            Some(Error::DurationTooLong)
+        );
+        let timer = builder
+            .default_timeout(Duration::from_secs(1))
+            .max_lifetime(Duration::from_secs(2))
+            .build()
+            .unwrap();
+        assert_eq!(timer.default_timeout(), Duration::from_secs(1));
+        assert_eq!(timer.max_lifetime(), Some(Duration::from_secs(2)));
+    }
+    #[test]
+    fn test_initial_timeout() {
+        let start = Instant::now();
+        tokio_test::block_on(async {
+            let timer = Timeout::new(runtime::Tokio::new(), Duration::from_millis(300))
+                .with_initial_timeout(Duration::from_millis(100));
+            assert!(timer.in_initial_phase());
+            timer.update_default_timeout(Duration::from_millis(200));
+            timer.wait().await;
+            assert!(start.elapsed() < Duration::from_millis(200));
+            timer.reset();
+            assert!(!timer.in_initial_phase());
+            assert!(timer.remaining().unwrap() > Duration::from_millis(100));
+            timer.wait().await;
+        });
+        assert!(start.elapsed() >= Duration::from_millis(300));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+    #[test]
+    fn test_phases() {
+        let builder = Timeout::builder(runtime::Tokio::new())
+            .phase("a", Duration::from_secs(1))
+            .phase("b", Duration::from_secs(2));
+        assert_eq!(
+            builder.clone().phase("a", Duration::ZERO).build().err(),
+            Some(Error::DuplicatePhase)
+        );
+        assert_eq!(
+            builder.clone().start_phase("c").build().err(),
+            Some(Error::UnknownPhase)
+        );
+        assert_eq!(
+            builder
+                .clone()
+                .default_timeout(Duration::MAX)
+                .start_phase("a")
+                .build()
+                .err(),
+            Some(Error::ConflictingDefaultTimeout)
+        );
+        let timer = builder.start_phase("a").build().unwrap();
+        assert_eq!(timer.phase(), Some("a"));
+        assert_eq!(timer.default_timeout(), Duration::from_secs(1));
+        assert_eq!(timer.set_phase("c"), Err(Error::UnknownPhase));
+        timer.set_phase_and_reset("b").unwrap();
+        assert_eq!(timer.phase(), Some("b"));
+        assert!(timer.remaining().unwrap() > Duration::from_secs(1));
+        timer.set_default_timeout(Duration::from_secs(5));
+        assert_eq!(timer.phase(), None);
+        assert_eq!(timer.default_timeout(), Duration::from_secs(5));
+        timer.set_phase("a").unwrap();
+        timer.update_default_timeout(Duration::from_secs(5));
+        assert_eq!(timer.phase(), None);
+    }
+    #[cfg(all(feature = "read-write", feature = "tokio"))]
+    #[test]
+    fn test_initial_timeout_wrapper() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        tokio_test::block_on(async {
+            let timer = Timeout::new(runtime::Tokio::new(), Duration::from_secs(10))
+                .with_initial_timeout(Duration::from_millis(100));
+            let io = tokio_test::io::Builder::new()
+                .write(b"hello")
+                .read(b"world")
+                .build();
+            let mut io = Wrapper::new(io, &timer);
+            io.write_all(b"hello").await.unwrap();
+            assert!(timer.in_initial_phase());
+            assert!(timer.remaining().unwrap() <= Duration::from_millis(100));
+            let mut buf = [0; 5];
+            io.read_exact(&mut buf).await.unwrap();
+            assert!(!timer.in_initial_phase());
+            assert!(timer.remaining().unwrap() > Duration::from_secs(1));
+        });
+    }
+    #[test]
+    fn test_warning() {
+        let start = Instant::now();
+        tokio_test::block_on(async {
+            let timer = Timeout::new(runtime::Tokio::new(), Duration::from_millis(200));
+            timer.wait_warning(Duration::from_millis(100)).await;
+            assert!(start.elapsed() >= Duration::from_millis(100));
+            assert!(!timer.is_expired());
+            timer.reset();
+            let (_, reason) = tokio::join!(
+                timer.wait_warning(Duration::from_millis(100)),
+                timer.wait_with_reason()
+            );
+            assert_eq!(reason, ExpiryReason::Idle);
+            timer.reset();
+            timer.pause();
+            tokio::join!(timer.wait_warning(Duration::from_millis(100)), async {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                timer.reset();
+                timer.resume();
+            });
+        });
+        assert!(start.elapsed() >= Duration::from_millis(450));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_on_expire() {
+        use std::sync::Mutex;
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        tokio_test::block_on(async {
+            let timer = SharedTimeout::new(runtime::Tokio::new(), Duration::from_millis(100));
+            let calls1 = calls.clone();
+            timer.on_expire(move |reason, idle| calls1.lock().unwrap().push((reason, idle)));
+            let calls1 = calls.clone();
+            let task = timer.spawn_on_expire(move |reason, idle| {
+                calls1.lock().unwrap().push((reason, idle));
+            });
+            tokio::join!(timer.wait(), timer.wait());
+            task.await.unwrap();
+            timer.wait().await;
+            assert_eq!(calls.lock().unwrap().len(), 2);
+            let calls1 = calls.clone();
+            timer.on_expire(move |reason, idle| calls1.lock().unwrap().push((reason, idle)));
+            let guard = timer.activity_guard();
+            timer.reset();
+            timer.expire_now();
+            timer.wait().await;
+            drop(guard);
+        });
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.len(), 3);
+        assert!(calls[..2]
+            .iter()
+            .all(|&(reason, idle)| reason == ExpiryReason::Idle
+                && idle >= Duration::from_millis(100)));
+        assert_eq!(calls[2].0, ExpiryReason::Cancelled);
+    }
+    #[cfg(feature = "stream")]
+    #[test]
+    fn test_watchdog() {
+        use futures_util::{FutureExt, StreamExt};
+        tokio_test::block_on(async {
+            let timer = Timeout::new(runtime::Tokio::new(), Duration::from_millis(50));
+            let mut watchdog = timer.watchdog();
+            let mut activity = timer.activity();
+            let expiry = watchdog.next().await.unwrap();
+            assert_eq!(expiry.reason(), ExpiryReason::Idle);
+            assert_eq!(expiry.missed(), 0);
+            assert!(!timer.is_expired());
+            // Re-arming isn't activity
+            assert!(timer.idle_for() >= Duration::from_millis(50));
+            assert!(activity.next().now_or_never().is_none());
+            tokio::time::sleep(Duration::from_millis(170)).await;
+            assert_eq!(watchdog.next().await.unwrap().missed(), 2);
+            timer.expire_now();
+            let expiry = watchdog.next().await.unwrap();
+            assert_eq!(expiry.reason(), ExpiryReason::Cancelled);
+            assert_eq!(watchdog.next().await, None);
+        });
+    }
+    #[cfg(feature = "stream")]
+    #[test]
+    fn test_watchdog_zero_period() {
+        use futures_util::StreamExt;
+        tokio_test::block_on(async {
+            let timer = Timeout::new(runtime::Tokio::new(), Duration::ZERO);
+            let mut watchdog = timer.watchdog();
+            let expiry = watchdog.next().await.unwrap();
+            assert_eq!(expiry.reason(), ExpiryReason::Idle);
+            assert_eq!(expiry.missed(), 0);
+            assert_eq!(watchdog.next().await, None);
+        });
+    }
+    #[cfg(feature = "stream")]
+    #[test]
+    fn test_heartbeats() {
+        use futures_util::StreamExt;
+        let start = Instant::now();
+        tokio_test::block_on(async {
+            let timer = Timeout::new(runtime::Tokio::new(), Duration::from_millis(250));
+            let mut heartbeats = timer.heartbeats(Duration::from_millis(100));
+            tokio::time::sleep(Duration::from_millis(60)).await;
+            timer.reset();
+            let idle = heartbeats.next().await.unwrap();
+            assert!(idle >= Duration::from_millis(100));
+            assert!(start.elapsed() >= Duration::from_millis(160));
+            let idle = heartbeats.next().await.unwrap();
+            assert!(idle >= Duration::from_millis(200));
+            assert_eq!(heartbeats.next().await, None);
+            // The stream ends at the deadline rather than when the next heartbeat is due
+            assert!(start.elapsed() < Duration::from_millis(350));
+        });
+        assert!(start.elapsed() >= Duration::from_millis(310));
+    }
+    #[test]
+    fn test_wait_for_activity() {
+        tokio_test::block_on(async {
+            let timer = Timeout::new(runtime::Tokio::new(), Duration::from_millis(50));
+            let activity = timer.wait_for_activity();
+            timer.extend_by(Duration::from_millis(10));
+            timer.reset();
+            activity.await;
+            timer.wait().await;
+            tokio::join!(timer.wait_for_activity(), async {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                assert!(timer.is_expired());
+                timer.reset();
+            });
+            assert!(!timer.is_expired());
+        });
+    }
+    #[cfg(feature = "stream")]
+    #[test]
+    fn test_activity_stream() {
+        use futures_util::StreamExt;
+        tokio_test::block_on(async {
+            let timer = Timeout::new(runtime::Tokio::new(), Duration::from_secs(10));
+            let mut activity = timer.activity();
+            timer.reset();
+            timer.reset();
+            assert_eq!(activity.next().await, Some(2));
+            let (count, _) = tokio::join!(activity.next(), async {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                timer.reset();
+            });
+            assert_eq!(count, Some(1));
+        });
+    }
+    #[cfg(feature = "stream")]
+    #[test]
+    fn test_countdown() {
+        use futures_util::StreamExt;
+        let start = Instant::now();
+        tokio_test::block_on(async {
+            let timer = Timeout::new(runtime::Tokio::new(), Duration::from_millis(250));
+            let mut countdown = timer.countdown(Duration::from_millis(100));
+            let remaining = countdown.next().await.unwrap();
+            assert!(remaining > Duration::from_millis(200));
+            let remaining = countdown.next().await.unwrap();
+            assert!(remaining <= Duration::from_millis(150));
+            assert!(remaining > Duration::from_millis(100));
+            let (remaining, _) = tokio::join!(countdown.next(), async {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                timer.reset();
+            });
+            assert!(remaining.unwrap() > Duration::from_millis(200));
+            assert!(start.elapsed() < Duration::from_millis(200));
+            let mut count = 0;
+            while countdown.next().await.is_some() {
+                count += 1;
+            }
+            assert_eq!(count, 2);
+            assert!(timer.is_expired());
+        });
+        assert!(start.elapsed() >= Duration::from_millis(400));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+    #[cfg(feature = "stream")]
+    #[test]
+    #[should_panic = "countdown tick must be non-zero"]
+    fn test_countdown_zero_tick() {
+        let timer = Timeout::new(runtime::Tokio::new(), Duration::from_secs(10));
+        let _ = timer.countdown(Duration::ZERO);
+    }
+    #[cfg(feature = "stream")]
+    #[test]
+    #[should_panic = "heartbeat interval must be non-zero"]
+    fn test_heartbeats_zero_interval() {
+        let timer = Timeout::new(runtime::Tokio::new(), Duration::from_secs(10));
+        let _ = timer.heartbeats(Duration::ZERO);
+    }
 }
@@ -0,0 +1,50 @@
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::{runtime::Runtime, Elapsed, Wait};
+
+pin_project_lite::pin_project! {
+    /// A future that races another future against a [`Timeout`](crate::Timeout).
+    ///
+    /// Created by [`Timeout::run`](crate::Timeout::run). Resolves to the output of the inner
+    /// future, or to [`Elapsed`] if the timeout expires first.
+    #[must_use = "futures do nothing unless you `.await` or poll them"]
+    pub struct Run<'a, R: Runtime, F> {
+        wait: Wait<'a, R>,
+        #[pin]
+        future: F,
+    }
+}
+
+impl<'a, R: Runtime, F> Run<'a, R, F> {
+    pub(crate) fn new(wait: Wait<'a, R>, future: F) -> Self {
+        Self { wait, future }
+    }
+    /// A reference to the inner future
+    pub fn inner(&self) -> &F {
+        &self.future
+    }
+    /// A pinned mutable reference to the inner future
+    pub fn inner_pin_mut(self: Pin<&mut Self>) -> Pin<&mut F> {
+        self.project().future
+    }
+}
+
+impl<R: Runtime, F: Future> Future for Run<'_, R, F> {
+    type Output = Result<F::Output, Elapsed>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let pinned = self.project();
+        // The future is polled first, so that it may complete even if the timeout has expired
+        // in the meantime
+        if let Poll::Ready(x) = pinned.future.poll(cx) {
+            return Poll::Ready(Ok(x));
+        }
+        pinned
+            .wait
+            .poll_expired(cx)
+            .map(|reason| Err(Elapsed::new(reason)))
+    }
+}
@@ -39,4 +39,8 @@ pub trait Runtime {
 pub trait Instant {
     /// Duration since an earlier instant.
     fn duration_since(&self, earlier: &Self) -> Duration;
+    /// The instant `duration` after this one, or `None` if it can't be represented.
+    fn checked_add(&self, duration: Duration) -> Option<Self>
+    where
+        Self: Sized;
 }
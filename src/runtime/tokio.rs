@@ -37,6 +37,9 @@ impl super::Instant for Instant {
     fn duration_since(&self, earlier: &Self) -> Duration {
         self.duration_since(*earlier)
     }
+    fn checked_add(&self, duration: Duration) -> Option<Self> {
+        Instant::checked_add(self, duration)
+    }
 }
 
 impl super::Sleep for Sleep {
@@ -0,0 +1,256 @@
+use core::{ops::Deref, time::Duration};
+use std::sync::{Arc, Weak};
+
+#[cfg(feature = "wrapper")]
+use crate::Wrapper;
+#[cfg(feature = "tokio")]
+use crate::{runtime, ExpiryReason};
+use crate::{runtime::Runtime, ActivityGuard, CowTimeout, Timeout, Wait};
+
+/// A cheaply cloneable, reference-counted [`Timeout`].
+///
+/// This dereferences to [`Timeout`], so all of its methods can be used directly. Use
+/// [`SharedTimeout::handle`] to give out a handle that can only reset the timeout.
+///
+/// # Example
+///
+/// ```
+/// # async fn example_fn() {
+/// use std::time::Duration;
+/// use async_shared_timeout::{runtime, SharedTimeout};
+///
+/// let timeout = SharedTimeout::new(runtime::Tokio::new(), Duration::from_secs(10));
+/// let handle = timeout.handle();
+/// tokio::spawn(async move {
+///     // This task can only reset the timeout
+///     handle.reset();
+/// });
+/// let watcher = tokio::spawn(timeout.wait_owned());
+/// # drop(watcher);
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub struct SharedTimeout<R: Runtime> {
+    timeout: Arc<Timeout<R>>,
+}
+
+impl<R: Runtime> SharedTimeout<R> {
+    /// Create a new shared timeout that expires after `default_timeout`. See [`Timeout::new`].
+    #[must_use]
+    pub fn new(runtime: R, default_timeout: Duration) -> Self {
+        Timeout::new(runtime, default_timeout).into()
+    }
+    /// A handle that can only reset the timeout
+    pub fn handle(&self) -> TimeoutHandle<R> {
+        TimeoutHandle {
+            timeout: self.timeout.clone(),
+        }
+    }
+    /// A weak handle that can reset the timeout without keeping it alive
+    pub fn downgrade(&self) -> WeakTimeout<R> {
+        WeakTimeout {
+            timeout: Arc::downgrade(&self.timeout),
+        }
+    }
+    /// Wait for the timeout to expire without borrowing it. See [`Timeout::wait_owned`].
+    pub fn wait_owned(&self) -> Wait<'static, R> {
+        Wait::new(CowTimeout::Arc(self.timeout.clone()))
+    }
+    /// Hold the timeout open until the returned guard is dropped, keeping it alive rather than
+    /// borrowing it. See [`Timeout::activity_guard`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn handle_request() {}
+    /// # async fn example_fn() {
+    /// use std::time::Duration;
+    /// use async_shared_timeout::{runtime, SharedTimeout};
+    ///
+    /// let timeout = SharedTimeout::new(runtime::Tokio::new(), Duration::from_secs(10));
+    /// let guard = timeout.activity_guard();
+    /// tokio::spawn(async move {
+    ///     handle_request().await;
+    ///     drop(guard);
+    /// });
+    /// # }
+    /// ```
+    pub fn activity_guard(&self) -> ActivityGuard<'static, R> {
+        ActivityGuard::new(CowTimeout::Arc(self.timeout.clone()))
+    }
+    /// Create a wrapper using this timeout. See [`Wrapper::new`].
+    #[cfg(feature = "wrapper")]
+    #[cfg_attr(docsrs, doc(cfg(all(feature = "std", feature = "wrapper"))))]
+    pub fn wrap<T>(&self, inner: T) -> Wrapper<'static, R, T> {
+        Wrapper::new_arc(inner, self.timeout.clone())
+    }
+    /// The underlying `Arc` pointer
+    pub fn as_arc(&self) -> &Arc<Timeout<R>> {
+        &self.timeout
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl SharedTimeout<runtime::Tokio> {
+    /// Register an expiry hook (see [`Timeout::on_expire`]) and spawn a tokio task that waits
+    /// for the expiry, so the hook gets called even if nothing else waits for the timeout.
+    ///
+    /// The task keeps the timeout alive until it expires; abort it through the returned handle
+    /// if the hook is no longer needed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # async fn example_fn() {
+    /// use std::time::Duration;
+    /// use async_shared_timeout::{runtime, SharedTimeout};
+    ///
+    /// let timeout = SharedTimeout::new(runtime::Tokio::new(), Duration::from_secs(10));
+    /// timeout.spawn_on_expire(|reason, idle| println!("timed out ({reason:?}) after {idle:?}"));
+    /// # }
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(all(feature = "std", feature = "tokio"))))]
+    pub fn spawn_on_expire(
+        &self,
+        hook: impl FnOnce(ExpiryReason, Duration) + Send + 'static,
+    ) -> tokio::task::JoinHandle<()> {
+        self.on_expire(hook);
+        let wait = self.wait_owned();
+        tokio::spawn(async move {
+            wait.await;
+        })
+    }
+}
+
+impl<R: Runtime> Clone for SharedTimeout<R> {
+    fn clone(&self) -> Self {
+        Self {
+            timeout: self.timeout.clone(),
+        }
+    }
+}
+
+impl<R: Runtime> Deref for SharedTimeout<R> {
+    type Target = Timeout<R>;
+    fn deref(&self) -> &Timeout<R> {
+        &self.timeout
+    }
+}
+
+impl<R: Runtime> From<Timeout<R>> for SharedTimeout<R> {
+    fn from(timeout: Timeout<R>) -> Self {
+        Arc::new(timeout).into()
+    }
+}
+
+impl<R: Runtime> From<Arc<Timeout<R>>> for SharedTimeout<R> {
+    fn from(timeout: Arc<Timeout<R>>) -> Self {
+        Self { timeout }
+    }
+}
+
+impl<R: Runtime> From<SharedTimeout<R>> for Arc<Timeout<R>> {
+    fn from(timeout: SharedTimeout<R>) -> Self {
+        timeout.timeout
+    }
+}
+
+/// A cheaply cloneable handle to a [`SharedTimeout`] that can reset the timeout, but can't wait
+/// for it or change its configuration.
+///
+/// Created by [`SharedTimeout::handle`].
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub struct TimeoutHandle<R: Runtime> {
+    timeout: Arc<Timeout<R>>,
+}
+
+impl<R: Runtime> TimeoutHandle<R> {
+    /// A weak handle that can reset the timeout without keeping it alive
+    pub fn downgrade(&self) -> WeakTimeout<R> {
+        WeakTimeout {
+            timeout: Arc::downgrade(&self.timeout),
+        }
+    }
+    /// Reset the timeout to the default time. See [`Timeout::reset`].
+    pub fn reset(&self) {
+        self.timeout.reset();
+    }
+    /// Reset the timeout to expire after `timeout`. See [`Timeout::reset_with`].
+    pub fn reset_with(&self, timeout: Duration) {
+        self.timeout.reset_with(timeout);
+    }
+    /// Reset the timeout to expire at `deadline`. See [`Timeout::reset_at`].
+    pub fn reset_at(&self, deadline: R::Instant) {
+        self.timeout.reset_at(deadline);
+    }
+    /// Push the current deadline further by `duration`. See [`Timeout::extend_by`].
+    pub fn extend_by(&self, duration: Duration) {
+        self.timeout.extend_by(duration);
+    }
+    /// Whether the timeout has expired. See [`Timeout::is_expired`].
+    pub fn is_expired(&self) -> bool {
+        self.timeout.is_expired()
+    }
+    /// Time left until the timeout expires. See [`Timeout::remaining`].
+    pub fn remaining(&self) -> Option<Duration> {
+        self.timeout.remaining()
+    }
+}
+
+impl<R: Runtime> Clone for TimeoutHandle<R> {
+    fn clone(&self) -> Self {
+        Self {
+            timeout: self.timeout.clone(),
+        }
+    }
+}
+
+/// A weak handle to a [`SharedTimeout`] that doesn't keep the timeout alive.
+///
+/// Created by [`SharedTimeout::downgrade`] or [`TimeoutHandle::downgrade`]. Once all
+/// [`SharedTimeout`]s and [`TimeoutHandle`]s are dropped, resetting does nothing. This is useful
+/// for long-lived background tasks that shouldn't keep connection state around.
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub struct WeakTimeout<R: Runtime> {
+    timeout: Weak<Timeout<R>>,
+}
+
+impl<R: Runtime> WeakTimeout<R> {
+    /// Upgrade to a [`TimeoutHandle`], or return `None` if the timeout has been dropped
+    pub fn upgrade(&self) -> Option<TimeoutHandle<R>> {
+        self.timeout
+            .upgrade()
+            .map(|timeout| TimeoutHandle { timeout })
+    }
+    /// Reset the timeout to the default time. See [`Timeout::reset`].
+    ///
+    /// Returns `false` without doing anything if the timeout has been dropped.
+    pub fn reset(&self) -> bool {
+        self.timeout.upgrade().map(|x| x.reset()).is_some()
+    }
+    /// Reset the timeout to expire after `timeout`. See [`Timeout::reset_with`].
+    ///
+    /// Returns `false` without doing anything if the timeout has been dropped.
+    pub fn reset_with(&self, timeout: Duration) -> bool {
+        self.timeout
+            .upgrade()
+            .map(|x| x.reset_with(timeout))
+            .is_some()
+    }
+    /// Whether the timeout has been dropped
+    pub fn is_dropped(&self) -> bool {
+        self.timeout.strong_count() == 0
+    }
+}
+
+impl<R: Runtime> Clone for WeakTimeout<R> {
+    fn clone(&self) -> Self {
+        Self {
+            timeout: self.timeout.clone(),
+        }
+    }
+}
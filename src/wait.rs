@@ -0,0 +1,118 @@
+use core::{
+    future::{Future, IntoFuture},
+    pin::Pin,
+    sync::atomic::Ordering,
+    task::{Context, Poll},
+};
+
+use crate::{runtime::Runtime, CowTimeout, ExpiryReason, Timeout};
+
+/// A future that completes when a [`Timeout`] expires, resolving to the reason of the expiry.
+///
+/// Created by [`Timeout::wait_with_reason`], [`Timeout::wait_owned`], or by awaiting a
+/// `&Timeout` directly. Unlike
+/// [`Timeout::wait`], this type can be named and is [`Unpin`], so it can be stored in a struct
+/// and driven manually with [`Wait::poll_expired`].
+///
+/// # Example
+///
+/// ```
+/// # async fn example_fn() {
+/// use std::time::Duration;
+/// use async_shared_timeout::{ExpiryReason, TokioTimeout};
+///
+/// let timeout = TokioTimeout::new_tokio(Duration::from_millis(10));
+/// assert_eq!((&timeout).await, ExpiryReason::Idle);
+/// # }
+/// ```
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Wait<'a, R: Runtime> {
+    timeout: CowTimeout<'a, R>,
+    // The number of `expire_now` calls that have already been observed
+    cancels: u64,
+    // The key in the timeout's waiter list
+    key: Option<usize>,
+}
+
+impl<'a, R: Runtime> Wait<'a, R> {
+    pub(crate) fn new(timeout: CowTimeout<'a, R>) -> Self {
+        let cancels = timeout.as_ref().cancels.load(Ordering::SeqCst);
+        Self {
+            timeout,
+            cancels,
+            key: None,
+        }
+    }
+
+    /// The timeout reference
+    pub fn timeout(&self) -> &Timeout<R> {
+        self.timeout.as_ref()
+    }
+
+    /// Poll for the timeout expiry. This is what the [`Future`] implementation uses, and it
+    /// follows the same rules: it returns [`Poll::Ready`] with the reason of the expiry once the
+    /// timeout has expired, and arranges for the current task to be woken up otherwise.
+    pub fn poll_expired(&mut self, cx: &mut Context<'_>) -> Poll<ExpiryReason> {
+        let Self {
+            timeout,
+            cancels,
+            key,
+        } = self;
+        let timeout = timeout.as_ref();
+        timeout.waiters.lock().register(key, cx.waker());
+        // `expire_now` wakes us up after counting the call, so it can't be missed even if the
+        // timeout is brought back to life before we're polled
+        let current_cancels = timeout.cancels.load(Ordering::SeqCst);
+        if current_cancels != *cancels {
+            *cancels = current_cancels;
+            if let Some(key) = key.take() {
+                timeout.waiters.lock().remove(key);
+            }
+            timeout.run_callbacks(ExpiryReason::Cancelled);
+            return Poll::Ready(ExpiryReason::Cancelled);
+        }
+        loop {
+            let target = timeout.arm();
+            let elapsed = timeout.elapsed_nanos();
+            if elapsed >= target {
+                if let Some(key) = key.take() {
+                    timeout.waiters.lock().remove(key);
+                }
+                let reason = timeout.reason_at(elapsed);
+                timeout.run_callbacks(reason);
+                return Poll::Ready(reason);
+            }
+            if target == u64::MAX {
+                // Nothing to sleep for
+                return Poll::Pending;
+            }
+            if timeout.poll_sleep_until(target, elapsed).is_pending() {
+                return Poll::Pending;
+            }
+            // The sleep has fired, the deadline may have been pushed further in the meantime
+        }
+    }
+}
+
+impl<R: Runtime> Future for Wait<'_, R> {
+    type Output = ExpiryReason;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.get_mut().poll_expired(cx)
+    }
+}
+
+impl<R: Runtime> Drop for Wait<'_, R> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.timeout.as_ref().waiters.lock().remove(key);
+        }
+    }
+}
+
+impl<'a, R: Runtime> IntoFuture for &'a Timeout<R> {
+    type Output = ExpiryReason;
+    type IntoFuture = Wait<'a, R>;
+    fn into_future(self) -> Self::IntoFuture {
+        Wait::new(CowTimeout::Ref(self))
+    }
+}
@@ -0,0 +1,117 @@
+//! Bookkeeping for futures waiting on a single [`Timeout`](crate::Timeout)
+use alloc::{boxed::Box, sync::Arc, task::Wake, vec::Vec};
+use core::{
+    pin::Pin,
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+
+use crate::{runtime::Sleep, sync::Mutex};
+
+/// A list of wakers of the futures waiting on a timeout, used to wake all of them up when the
+/// deadline moves earlier than the one they're sleeping until, or when the shared sleep fires.
+#[derive(Debug, Default)]
+pub(crate) struct Waiters {
+    wakers: Vec<Option<Waker>>,
+    free: Vec<usize>,
+}
+
+impl Waiters {
+    /// Register or update the waker of a waiter
+    pub(crate) fn register(&mut self, key: &mut Option<usize>, waker: &Waker) {
+        match *key {
+            Some(key) => {
+                let current = self.wakers[key].as_mut().unwrap();
+                if !current.will_wake(waker) {
+                    *current = waker.clone();
+                }
+            }
+            None => {
+                let new_key = if let Some(new_key) = self.free.pop() {
+                    self.wakers[new_key] = Some(waker.clone());
+                    new_key
+                } else {
+                    self.wakers.push(Some(waker.clone()));
+                    self.wakers.len() - 1
+                };
+                *key = Some(new_key);
+            }
+        }
+    }
+
+    /// Remove a waiter
+    pub(crate) fn remove(&mut self, key: usize) {
+        self.wakers[key] = None;
+        self.free.push(key);
+    }
+
+    /// The wakers of all waiters
+    pub(crate) fn all(&self) -> Vec<Waker> {
+        self.wakers.iter().flatten().cloned().collect()
+    }
+}
+
+/// Waking the list wakes every waiter up. This is the waker the shared sleep is polled with, so
+/// it doesn't matter which waiter polled it last.
+impl Wake for Mutex<Waiters> {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+    fn wake_by_ref(self: &Arc<Self>) {
+        let wakers = self.lock().all();
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+/// The single sleep shared by all waiters of a timeout. Whichever waiter is polled arms it for
+/// the earliest point in time it needs, so a waiter that's never polled again doesn't hold the
+/// rest back.
+#[derive(Debug)]
+pub(crate) struct SharedSleep<S> {
+    // Only created once there's something to sleep until
+    sleep: Option<Pin<Box<S>>>,
+    // The point in time `sleep` is armed for, or `u64::MAX` if it isn't armed
+    armed_ns: u64,
+}
+
+impl<S> Default for SharedSleep<S> {
+    fn default() -> Self {
+        Self {
+            sleep: None,
+            armed_ns: u64::MAX,
+        }
+    }
+}
+
+impl<S: Sleep> SharedSleep<S> {
+    /// Make sure the sleep fires no later than `target`, and poll it with `waker`. `elapsed` is
+    /// the current time, and `create_sleep` creates the sleep the first time it's needed.
+    ///
+    /// Once the sleep fires, it's disarmed until a waiter arms it again.
+    pub(crate) fn poll_until(
+        &mut self,
+        target: u64,
+        elapsed: u64,
+        create_sleep: impl FnOnce(Duration) -> S,
+        waker: &Waker,
+    ) -> Poll<()> {
+        if target < self.armed_ns {
+            let remaining = Duration::from_nanos(target.saturating_sub(elapsed));
+            match &mut self.sleep {
+                Some(sleep) => sleep.as_mut().reset(remaining),
+                None => self.sleep = Some(Box::pin(create_sleep(remaining))),
+            }
+            self.armed_ns = target;
+        }
+        let Some(sleep) = &mut self.sleep else {
+            return Poll::Pending;
+        };
+        let poll = sleep.as_mut().poll_sleep(&mut Context::from_waker(waker));
+        if poll.is_ready() {
+            self.armed_ns = u64::MAX;
+        }
+        poll
+    }
+}
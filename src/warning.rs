@@ -0,0 +1,72 @@
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::{runtime::Runtime, CowTimeout, Timeout};
+
+/// A future that completes when a [`Timeout`] is about to expire.
+///
+/// Created by [`Timeout::wait_warning`].
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Warning<'a, R: Runtime> {
+    timeout: CowTimeout<'a, R>,
+    lead_ns: u64,
+    // The key in the timeout's waiter list
+    key: Option<usize>,
+}
+
+impl<'a, R: Runtime> Warning<'a, R> {
+    pub(crate) fn new(timeout: CowTimeout<'a, R>, lead_ns: u64) -> Self {
+        Self {
+            timeout,
+            lead_ns,
+            key: None,
+        }
+    }
+
+    /// The timeout reference
+    pub fn timeout(&self) -> &Timeout<R> {
+        self.timeout.as_ref()
+    }
+
+    /// Poll for the warning. This is what the [`Future`] implementation uses.
+    pub fn poll_warning(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        let timeout = self.timeout.as_ref();
+        // Registering is what makes deadline changes, resuming and releasing guards wake us up
+        timeout.waiters.lock().register(&mut self.key, cx.waker());
+        loop {
+            let deadline = timeout.arm();
+            if deadline == u64::MAX {
+                return Poll::Pending;
+            }
+            let target = deadline.saturating_sub(self.lead_ns);
+            let elapsed = timeout.elapsed_nanos();
+            if elapsed >= target {
+                if let Some(key) = self.key.take() {
+                    timeout.waiters.lock().remove(key);
+                }
+                return Poll::Ready(());
+            }
+            if timeout.poll_sleep_until(target, elapsed).is_pending() {
+                return Poll::Pending;
+            }
+        }
+    }
+}
+
+impl<R: Runtime> Future for Warning<'_, R> {
+    type Output = ();
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.get_mut().poll_warning(cx)
+    }
+}
+
+impl<R: Runtime> Drop for Warning<'_, R> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.timeout.as_ref().waiters.lock().remove(key);
+        }
+    }
+}
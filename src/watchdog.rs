@@ -0,0 +1,95 @@
+use core::{
+    pin::Pin,
+    sync::atomic::Ordering,
+    task::{Context, Poll},
+};
+use futures_core::{ready, FusedStream, Stream};
+
+use crate::{runtime::Runtime, CowTimeout, ExpiryReason, Timeout, Wait};
+
+/// An expiry reported by a [`Watchdog`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+pub struct Expiry {
+    reason: ExpiryReason,
+    missed: u64,
+}
+
+impl Expiry {
+    /// Why the timeout has expired
+    pub fn reason(&self) -> ExpiryReason {
+        self.reason
+    }
+    /// The number of whole default timeout periods that have passed since the expiry before
+    /// it was observed, i.e. how many expiries were missed because the stream wasn't polled.
+    pub fn missed(&self) -> u64 {
+        self.missed
+    }
+}
+
+/// A stream that yields every time a [`Timeout`] expires, re-arming it afterwards.
+///
+/// Created by [`Timeout::watchdog`]. After an idle expiry the deadline is moved one default
+/// timeout away, so the next item is yielded after another period of inactivity. This doesn't
+/// count as a [reset](Timeout::reset): [`Timeout::idle_for`] keeps growing, activity listeners
+/// aren't notified, and the [initial phase](Timeout::in_initial_phase) doesn't end.
+/// Expiries due to the [maximum lifetime](Timeout::with_max_lifetime) or
+/// [cancellation](Timeout::expire_now) can't be recovered from: they are yielded once, and then
+/// the stream ends. The same goes for an idle expiry while the default timeout is zero, since
+/// there's no period to re-arm the timeout for.
+#[must_use = "streams do nothing unless polled"]
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+pub struct Watchdog<'a, R: Runtime> {
+    wait: Wait<'a, R>,
+    done: bool,
+}
+
+impl<'a, R: Runtime> Watchdog<'a, R> {
+    pub(crate) fn new(timeout: CowTimeout<'a, R>) -> Self {
+        Self {
+            wait: Wait::new(timeout),
+            done: false,
+        }
+    }
+
+    /// The timeout reference
+    pub fn timeout(&self) -> &Timeout<R> {
+        self.wait.timeout()
+    }
+}
+
+impl<R: Runtime> Stream for Watchdog<'_, R> {
+    type Item = Expiry;
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(None);
+        }
+        let reason = ready!(this.wait.poll_expired(cx));
+        let timeout = this.wait.timeout();
+        let mut missed = 0;
+        if reason == ExpiryReason::Idle {
+            let overdue = timeout
+                .elapsed_nanos()
+                .saturating_sub(timeout.timeout_from_epoch_ns.load(Ordering::Acquire));
+            let period = timeout.default_timeout_nanos();
+            match overdue.checked_div(period) {
+                Some(periods) => {
+                    missed = periods;
+                    timeout.rearm();
+                }
+                // Re-arming with a zero default timeout would expire it again right away
+                None => this.done = true,
+            }
+        } else {
+            this.done = true;
+        }
+        Poll::Ready(Some(Expiry { reason, missed }))
+    }
+}
+
+impl<R: Runtime> FusedStream for Watchdog<'_, R> {
+    fn is_terminated(&self) -> bool {
+        self.done
+    }
+}
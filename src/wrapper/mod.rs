@@ -14,23 +14,7 @@ mod tokio_read_write;
 #[cfg(all(feature = "std", unix))]
 use std::os::unix::io::{AsRawFd, RawFd};
 
-use crate::{runtime::Runtime, Timeout};
-
-#[derive(Clone)]
-enum CowTimeout<'a, R: Runtime> {
-    #[cfg(feature = "std")]
-    Arc(Arc<Timeout<R>>),
-    Ref(&'a Timeout<R>),
-}
-impl<'a, R: Runtime> AsRef<Timeout<R>> for CowTimeout<'a, R> {
-    fn as_ref(&self) -> &Timeout<R> {
-        match self {
-            #[cfg(feature = "std")]
-            Self::Arc(x) => x,
-            Self::Ref(x) => x,
-        }
-    }
-}
+use crate::{runtime::Runtime, CowTimeout, Run, Timeout, Wait};
 
 pin_project_lite::pin_project! {
     /// A wrapper that wraps a future, a stream or an async reader/writer and resets the timeout
@@ -48,6 +32,9 @@ pin_project_lite::pin_project! {
     /// - In case of a [`Stream`](futures_core::Stream) object, timeout will be reset upon stream
     ///   advancement.
     ///
+    /// While the [initial timeout](Timeout::with_initial_timeout) is in effect, only reads,
+    /// future completion and stream advancement reset the timeout.
+    ///
     /// Since [`Wrapper::new`] accepts a shared reference to `Timeout`, you can make multiple
     /// objects use a single timeout. This means the timeout will only expire when *all* objects
     /// stopped having new events.
@@ -118,6 +105,14 @@ impl<'a, R: Runtime, T> Wrapper<'a, R, T> {
     pub fn inner_mut(&mut self) -> &mut T {
         &mut self.inner
     }
+    /// Run the wrapped future until it completes or the timeout expires, whichever happens
+    /// first. See [`Timeout::run`] for more info.
+    pub fn run(self) -> Run<'a, R, Self>
+    where
+        T: Future,
+    {
+        Run::new(Wait::new(self.timeout.clone()), self)
+    }
 }
 
 #[cfg(feature = "std")]
@@ -45,7 +45,7 @@ impl<R: Runtime, T: AsyncWrite> AsyncWrite for Wrapper<'_, R, T> {
         let pinned = self.project();
         match pinned.inner.poll_write(cx, buf) {
             Poll::Ready(Ok(written)) if written > 0 => {
-                pinned.timeout.as_ref().reset();
+                pinned.timeout.as_ref().reset_after_initial();
                 Poll::Ready(Ok(written))
             }
             x => x,
@@ -59,7 +59,7 @@ impl<R: Runtime, T: AsyncWrite> AsyncWrite for Wrapper<'_, R, T> {
         let pinned = self.project();
         match pinned.inner.poll_write_vectored(cx, bufs) {
             Poll::Ready(Ok(written)) if written > 0 => {
-                pinned.timeout.as_ref().reset();
+                pinned.timeout.as_ref().reset_after_initial();
                 Poll::Ready(Ok(written))
             }
             x => x,
@@ -96,7 +96,7 @@ impl<R: Runtime, T: AsyncSeek> AsyncSeek for Wrapper<'_, R, T> {
         let pinned = self.project();
         match pinned.inner.poll_complete(cx) {
             Poll::Ready(Ok(pos)) => {
-                pinned.timeout.as_ref().reset();
+                pinned.timeout.as_ref().reset_after_initial();
                 Poll::Ready(Ok(pos))
             }
             x => x,